@@ -0,0 +1,349 @@
+//! Known answers, stored as a small TOML file, to check solutions against
+//!
+//! ```toml
+//! [day1]
+//! part1 = 470
+//! part2 = 790
+//!
+//! [day7]
+//! part1 = "CABDFE"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use error::{Error, Result};
+use solution::{Answer, Value};
+use summary::table;
+
+/// Default location of the answers file.
+pub const DEFAULT_FILE: &str = "answers.toml";
+
+/// Known answers by day and part.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Answers {
+    known: BTreeMap<(u32, u8), Value>,
+}
+
+/// How a computed answer compares with the stored one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: Value,
+    },
+    /// Nothing stored for this day and part yet
+    New,
+}
+
+/// Result of checking one part of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub day: u32,
+    pub part: u8,
+    pub answer: Value,
+    pub status: Status,
+}
+
+impl Answers {
+    /// No known answers.
+    pub fn new() -> Self {
+        Answers {
+            known: BTreeMap::new(),
+        }
+    }
+
+    /// Parses `[dayN]` sections of `partN = value` lines.
+    pub fn parse(text: &str) -> Result<Answers> {
+        let mut answers = Answers::new();
+        let mut day = None;
+
+        for (n, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            let column = raw.len() - raw.trim_start().len() + 1;
+            let error = |msg: &str| Error::parse(n + 1, column, raw, msg);
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = &line[1..line.len() - 1];
+                day = match strip_number(name, "day") {
+                    Some(d) => Some(d),
+                    None => return Err(error("expected a [dayN] section")),
+                };
+                continue;
+            }
+
+            let day = match day {
+                Some(d) => d,
+                None => return Err(error("answer outside of a [dayN] section")),
+            };
+
+            let mut kv = line.splitn(2, '=');
+            let key = kv.next().unwrap_or("").trim();
+            let value = match kv.next() {
+                Some(v) => v.trim(),
+                None => return Err(error("expected partN = value")),
+            };
+
+            let part = match strip_number(key, "part") {
+                Some(p) if p == 1 || p == 2 => p as u8,
+                _ => return Err(error("expected part1 or part2")),
+            };
+
+            let value = parse_value(value).ok_or_else(|| error("invalid value"))?;
+            answers.set(day, part, value);
+        }
+
+        Ok(answers)
+    }
+
+    /// Loads answers from `path`. A missing file is treated as empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Answers> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text).map_err(|e| e.in_file(path.display().to_string())),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::new()),
+            Err(source) => Err(Error::Io {
+                context: format!("Failed to load answers from {}", path.display()),
+                source,
+            }),
+        }
+    }
+
+    /// Writes the answers to `path` in the format read by `load`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        fs::write(path, self.to_toml()).map_err(|source| Error::Io {
+            context: format!("Failed to save answers to {}", path.display()),
+            source,
+        })
+    }
+
+    /// The known answer to a part of a day, if any.
+    pub fn get(&self, day: u32, part: u8) -> Option<&Value> {
+        self.known.get(&(day, part))
+    }
+
+    /// Stores the answer to a part of a day, replacing any known one.
+    pub fn set(&mut self, day: u32, part: u8, value: Value) {
+        self.known.insert((day, part), value);
+    }
+
+    /// The answers in the format read by `parse`.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        let mut current = None;
+
+        for (&(day, part), value) in &self.known {
+            if current != Some(day) {
+                if current.is_some() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[day{}]\n", day));
+                current = Some(day);
+            }
+
+            let value = match *value {
+                Value::Number(n) => n.to_string(),
+                Value::Text(ref s) => {
+                    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+                }
+            };
+            out.push_str(&format!("part{} = {}\n", part, value));
+        }
+
+        out
+    }
+
+    /// Compares both parts of a computed answer with the stored ones.
+    pub fn check(&self, day: u32, answer: &Answer) -> Vec<Check> {
+        self.check_parts(day, &[(1, answer.part1.clone()), (2, answer.part2.clone())])
+    }
+
+    /// Compares answers to some of the parts, given with their part number.
+    pub fn check_parts(&self, day: u32, answers: &[(u8, Value)]) -> Vec<Check> {
+        answers
+            .iter()
+            .map(|&(part, ref value)| {
+                let status = match self.get(day, part) {
+                    Some(expected) if expected == value => Status::Pass,
+                    Some(expected) => Status::Fail {
+                        expected: expected.clone(),
+                    },
+                    None => Status::New,
+                };
+
+                Check {
+                    day,
+                    part,
+                    answer: value.clone(),
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    /// Stores the answers of `New` checks, leaving known answers untouched.
+    /// Returns how many answers were added.
+    pub fn record(&mut self, checks: &[Check]) -> usize {
+        let mut added = 0;
+
+        for c in checks {
+            if c.status == Status::New {
+                self.set(c.day, c.part, c.answer.clone());
+                added += 1;
+            }
+        }
+
+        added
+    }
+}
+
+fn strip_number(s: &str, prefix: &str) -> Option<u32> {
+    s.strip_prefix(prefix).and_then(|n| n.parse().ok())
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        let mut text = String::new();
+        let mut chars = s[1..s.len() - 1].chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(e @ '\\') | Some(e @ '"') => text.push(e),
+                    _ => return None,
+                },
+                '"' => return None,
+                c => text.push(c),
+            }
+        }
+
+        Some(Value::Text(text))
+    } else {
+        s.parse::<i128>().ok().map(Value::Number)
+    }
+}
+
+/// Renders the checks as a table with a row per part.
+pub fn render(checks: &[Check]) -> String {
+    let header = ["Day", "Part", "Answer", "Status"];
+
+    let cells: Vec<Vec<String>> = checks
+        .iter()
+        .map(|c| {
+            let status = match c.status {
+                Status::Pass => "PASS".to_string(),
+                Status::Fail { ref expected } => format!("FAIL (expected {})", expected),
+                Status::New => "NEW".to_string(),
+            };
+
+            vec![
+                c.day.to_string(),
+                c.part.to_string(),
+                c.answer.to_string(),
+                status,
+            ]
+        })
+        .collect();
+
+    table(&header, &cells)
+}
+
+#[test]
+fn test_parse() {
+    let answers = Answers::parse(
+        "# known answers\n\
+         [day1]\n\
+         part1 = 470\n\
+         part2 = -3\n\
+         \n\
+         [day7]\n\
+         part1 = \"CAB\\\"DFE\"\n\
+         \n\
+         [day9]\n\
+         part2 = 18446744073709551615\n",
+    )
+    .unwrap();
+
+    assert_eq!(answers.get(1, 1), Some(&Value::Number(470)));
+    assert_eq!(answers.get(1, 2), Some(&Value::Number(-3)));
+    assert_eq!(
+        answers.get(7, 1),
+        Some(&Value::Text("CAB\"DFE".to_string()))
+    );
+    assert_eq!(answers.get(7, 2), None);
+    assert_eq!(answers.get(9, 2), Some(&Value::from(u64::MAX)));
+}
+
+#[test]
+fn test_parse_errors() {
+    let message = |text| Answers::parse(text).unwrap_err().to_string();
+
+    assert_eq!(
+        message("part1 = 3"),
+        "line 1, column 1: answer outside of a [dayN] section: \"part1 = 3\""
+    );
+    assert_eq!(
+        message("[day1]\n  part3 = 3"),
+        "line 2, column 3: expected part1 or part2: \"  part3 = 3\""
+    );
+    assert!(Answers::parse("[dayone]").is_err());
+    assert!(Answers::parse("[day1]\npart1 = \"open").is_err());
+    assert!(Answers::parse("[day1]\npart1 = 1.5").is_err());
+}
+
+#[test]
+fn test_toml_round_trip() {
+    let mut answers = Answers::new();
+    answers.set(2, 2, Value::Text("a\\b\"c".to_string()));
+    answers.set(2, 1, Value::Number(8892));
+    answers.set(1, 1, Value::Number(470));
+
+    let toml = answers.to_toml();
+    assert_eq!(
+        toml,
+        "[day1]\npart1 = 470\n\n[day2]\npart1 = 8892\npart2 = \"a\\\\b\\\"c\"\n"
+    );
+    assert_eq!(Answers::parse(&toml).unwrap(), answers);
+}
+
+#[test]
+fn test_check_and_record() {
+    let mut answers = Answers::new();
+    answers.set(5, 1, Value::Number(10));
+
+    let answer = Answer {
+        part1: Value::Number(11),
+        part2: Value::Number(4),
+    };
+    let checks = answers.check(5, &answer);
+
+    assert_eq!(
+        checks[0].status,
+        Status::Fail {
+            expected: Value::Number(10)
+        }
+    );
+    assert_eq!(checks[1].status, Status::New);
+
+    assert_eq!(answers.record(&checks), 1);
+    assert_eq!(answers.get(5, 1), Some(&Value::Number(10)));
+    assert_eq!(answers.get(5, 2), Some(&Value::Number(4)));
+
+    let answer = Answer {
+        part1: Value::Number(10),
+        part2: Value::Number(4),
+    };
+    assert!(answers
+        .check(5, &answer)
+        .iter()
+        .all(|c| c.status == Status::Pass));
+}
@@ -0,0 +1,244 @@
+//! Repeated timing of the parse and solve steps of each day
+
+use std::hint;
+use std::time::{Duration, Instant};
+
+use summary::{format_duration, table};
+
+/// How many times to run each step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    /// Untimed runs before measuring
+    pub warmup: u32,
+    /// Timed runs, at least one is always made
+    pub iterations: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            warmup: 1,
+            iterations: 10,
+        }
+    }
+}
+
+/// Summary of the timed runs of a single step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    /// Panics if there are no samples.
+    pub fn from_samples(samples: &mut [Duration]) -> Stats {
+        assert!(!samples.is_empty(), "no samples to summarise");
+
+        samples.sort();
+        let n = samples.len();
+
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+
+        let total: Duration = samples.iter().sum();
+
+        Stats {
+            min: samples[0],
+            median,
+            mean: total / n as u32,
+        }
+    }
+}
+
+/// Times `f` according to `options`.
+pub fn measure<T, F: FnMut() -> T>(options: &Options, mut f: F) -> Stats {
+    for _ in 0..options.warmup {
+        hint::black_box(f());
+    }
+
+    let mut samples: Vec<Duration> = (0..options.iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            hint::black_box(f());
+            start.elapsed()
+        })
+        .collect();
+
+    Stats::from_samples(&mut samples)
+}
+
+/// Timings of every step of a day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bench {
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl Bench {
+    fn steps(&self) -> [(&'static str, &Stats); 3] {
+        [
+            ("parse", &self.parse),
+            ("part1", &self.part1),
+            ("part2", &self.part2),
+        ]
+    }
+}
+
+/// Benchmark outcome of a single day.
+pub struct DayBench {
+    pub day: u32,
+    pub result: Result<Bench, String>,
+}
+
+/// Renders the results as a table with a row per step.
+pub fn render(results: &[DayBench]) -> String {
+    let header = ["Day", "Step", "Min", "Median", "Mean"];
+    let mut cells = vec![];
+
+    for r in results {
+        match r.result {
+            Ok(ref b) => {
+                for &(step, stats) in &b.steps() {
+                    cells.push(vec![
+                        r.day.to_string(),
+                        step.to_string(),
+                        format_duration(stats.min),
+                        format_duration(stats.median),
+                        format_duration(stats.mean),
+                    ]);
+                }
+            }
+            Err(ref e) => cells.push(vec![
+                r.day.to_string(),
+                format!("FAILED: {}", e),
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+            ]),
+        }
+    }
+
+    table(&header, &cells)
+}
+
+/// Serialises the results as JSON with all durations in nanoseconds.
+pub fn to_json(results: &[DayBench], options: &Options, timestamp: &str) -> String {
+    let days: Vec<String> = results
+        .iter()
+        .map(|r| match r.result {
+            Ok(ref b) => {
+                let steps: Vec<String> = b
+                    .steps()
+                    .iter()
+                    .map(|&(step, s)| {
+                        format!(
+                            "\"{}\": {{\"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}}}",
+                            step,
+                            s.min.as_nanos(),
+                            s.median.as_nanos(),
+                            s.mean.as_nanos()
+                        )
+                    })
+                    .collect();
+                format!("    {{\"day\": {}, {}}}", r.day, steps.join(", "))
+            }
+            Err(ref e) => format!(
+                "    {{\"day\": {}, \"error\": \"{}\"}}",
+                r.day,
+                escape_json(e)
+            ),
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"timestamp\": \"{}\",\n  \"warmup\": {},\n  \"iterations\": {},\n  \"days\": [\n{}\n  ]\n}}\n",
+        escape_json(timestamp),
+        options.warmup,
+        options.iterations.max(1),
+        days.join(",\n")
+    )
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::new();
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[test]
+fn test_stats_from_samples() {
+    let ms = Duration::from_millis;
+
+    let stats = Stats::from_samples(&mut [ms(5), ms(1), ms(3)]);
+    assert_eq!(
+        stats,
+        Stats {
+            min: ms(1),
+            median: ms(3),
+            mean: ms(3),
+        }
+    );
+
+    let stats = Stats::from_samples(&mut [ms(4), ms(1), ms(2), ms(9)]);
+    assert_eq!(stats.median, ms(3));
+    assert_eq!(stats.mean, ms(4));
+}
+
+#[test]
+fn test_measure_runs_every_iteration() {
+    let mut calls = 0;
+    let options = Options {
+        warmup: 2,
+        iterations: 5,
+    };
+
+    measure(&options, || calls += 1);
+    assert_eq!(calls, 7);
+}
+
+#[test]
+fn test_to_json() {
+    let stats = Stats {
+        min: Duration::from_nanos(1),
+        median: Duration::from_nanos(2),
+        mean: Duration::from_nanos(3),
+    };
+    let results = vec![
+        DayBench {
+            day: 1,
+            result: Ok(Bench {
+                parse: stats,
+                part1: stats,
+                part2: stats,
+            }),
+        },
+        DayBench {
+            day: 7,
+            result: Err("bad \"input\"".to_string()),
+        },
+    ];
+
+    let json = to_json(&results, &Options::default(), "2018-12-07T05:00:00Z");
+
+    assert!(json.contains("\"timestamp\": \"2018-12-07T05:00:00Z\""));
+    assert!(json.contains(
+        "{\"day\": 1, \"parse\": {\"min_ns\": 1, \"median_ns\": 2, \"mean_ns\": 3}, \"part1\""
+    ));
+    assert!(json.contains("{\"day\": 7, \"error\": \"bad \\\"input\\\"\"}"));
+}
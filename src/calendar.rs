@@ -0,0 +1,128 @@
+//! Which 2018 puzzles have been released at a given time
+
+use std::fmt;
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+/// Year of the event these solutions are for.
+pub const YEAR: i32 = 2018;
+
+/// Number of puzzles in the event.
+pub const LAST_DAY: u32 = 25;
+
+/// Puzzles unlock at midnight in this offset from UTC (US Eastern, UTC-5).
+pub const RELEASE_OFFSET_HOURS: i64 = -5;
+
+/// Number of puzzles unlocked at `now`, from 0 before the event to 25 after.
+pub fn unlocked(now: DateTime<Utc>) -> u32 {
+    let release = (now + Duration::hours(RELEASE_OFFSET_HOURS)).naive_utc();
+
+    if release.year() < YEAR || (release.year() == YEAR && release.month() < 12) {
+        0
+    } else if release.year() == YEAR {
+        release.day().min(LAST_DAY)
+    } else {
+        LAST_DAY
+    }
+}
+
+/// The day picked when none is given, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultDay {
+    pub day: u32,
+    pub unlocked: u32,
+    pub reason: Reason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    /// Today's puzzle is the latest one unlocked and it is implemented
+    LatestUnlocked,
+    /// The latest unlocked puzzle isn't implemented, so the latest one
+    /// before it that is
+    LatestImplemented,
+    /// No puzzle is unlocked yet
+    NoneUnlocked,
+}
+
+impl fmt::Display for DefaultDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No day given, running day {}: ", self.day)?;
+
+        match self.reason {
+            Reason::LatestUnlocked => write!(f, "the latest unlocked {} puzzle", YEAR),
+            Reason::LatestImplemented => write!(
+                f,
+                "the latest implemented day ({} of {} puzzles of {} are unlocked)",
+                self.unlocked, LAST_DAY, YEAR
+            ),
+            Reason::NoneUnlocked => write!(
+                f,
+                "the latest implemented day (no {} puzzle is unlocked yet)",
+                YEAR
+            ),
+        }
+    }
+}
+
+/// Picks the latest unlocked puzzle at `now` that is in `implemented`,
+/// falling back to the latest implemented day. `None` if nothing is
+/// implemented.
+pub fn default_day(now: DateTime<Utc>, implemented: &[u32]) -> Option<DefaultDay> {
+    let unlocked = unlocked(now);
+    let latest = implemented.iter().cloned().max()?;
+
+    let (day, reason) = match implemented.iter().cloned().filter(|&d| d <= unlocked).max() {
+        Some(d) if d == unlocked => (d, Reason::LatestUnlocked),
+        Some(d) => (d, Reason::LatestImplemented),
+        None if unlocked == 0 => (latest, Reason::NoneUnlocked),
+        None => (latest, Reason::LatestImplemented),
+    };
+
+    Some(DefaultDay {
+        day,
+        unlocked,
+        reason,
+    })
+}
+
+#[cfg(test)]
+fn utc(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_unlocked() {
+    assert_eq!(unlocked(utc("2018-10-18T12:00:00Z")), 0);
+    // Day 1 unlocks at 05:00 UTC
+    assert_eq!(unlocked(utc("2018-12-01T04:59:59Z")), 0);
+    assert_eq!(unlocked(utc("2018-12-01T05:00:00Z")), 1);
+    assert_eq!(unlocked(utc("2018-12-10T03:00:00Z")), 9);
+    assert_eq!(unlocked(utc("2018-12-31T12:00:00Z")), 25);
+    assert_eq!(unlocked(utc("2026-10-18T12:00:00Z")), 25);
+}
+
+#[test]
+fn test_default_day() {
+    let implemented = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+    let pick = default_day(utc("2018-12-08T06:00:00Z"), &implemented).unwrap();
+    assert_eq!((pick.day, pick.reason), (8, Reason::LatestUnlocked));
+    assert_eq!(
+        pick.to_string(),
+        "No day given, running day 8: the latest unlocked 2018 puzzle"
+    );
+
+    let pick = default_day(utc("2019-10-18T12:00:00Z"), &implemented).unwrap();
+    assert_eq!((pick.day, pick.reason), (9, Reason::LatestImplemented));
+    assert_eq!(
+        pick.to_string(),
+        "No day given, running day 9: the latest implemented day (25 of 25 puzzles of 2018 are unlocked)"
+    );
+
+    let pick = default_day(utc("2018-10-18T12:00:00Z"), &implemented).unwrap();
+    assert_eq!((pick.day, pick.reason), (9, Reason::NoneUnlocked));
+
+    assert_eq!(default_day(utc("2018-12-08T06:00:00Z"), &[]), None);
+}
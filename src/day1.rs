@@ -1,27 +1,35 @@
 // https://adventofcode.com/2018/day/1
 
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, PartialEq)]
-struct Frequency(i32);
+use error::{self, Error, Result};
+use solution::Solution;
+
+/// Device frequency, starting from zero and adjusted by a list of changes.
+#[derive(Debug, PartialEq, Default)]
+pub struct Frequency(pub i32);
 
 impl Frequency {
-    fn new() -> Self {
+    /// Frequency at zero.
+    pub fn new() -> Self {
         Frequency(0)
     }
 
-    fn change(self, c: i32) -> Self {
+    /// Applies a single change.
+    pub fn change(self, c: i32) -> Self {
         let Frequency(x) = self;
         Frequency(x + c)
     }
 
-    fn add_changes(self, changes: &[i32]) -> Self {
+    /// Applies every change in order.
+    pub fn add_changes(self, changes: &[i32]) -> Self {
         changes.iter().fold(self, |f, y| f.change(*y))
     }
 
-    fn add_change_from_str(self, cstr: &str) -> Self {
+    /// Applies a change written as a signed number, e.g. `"+3"` or `" -7 "`.
+    ///
+    /// Panics if the change is not a number.
+    pub fn add_change_from_str(self, cstr: &str) -> Self {
         let num = cstr
             .trim()
             .parse::<i32>()
@@ -30,18 +38,26 @@ impl Frequency {
         self.change(num)
     }
 
-    fn add_changes_from_str(self, cstrs: &[&str]) -> Self {
+    /// Applies every change given as strings.
+    pub fn add_changes_from_str(self, cstrs: &[&str]) -> Self {
         cstrs
             .iter()
             .fold(self, |f, cstr| f.add_change_from_str(&cstr))
     }
 
-    fn value(&self) -> i32 {
+    /// Current frequency.
+    pub fn value(&self) -> i32 {
         let &Frequency(num) = self;
         num
     }
 
-    fn first_repeating_value(&self, changes: &[i32]) -> i32 {
+    /// First frequency reached twice while applying `changes` over and over,
+    /// or `None` if no frequency ever repeats.
+    pub fn first_repeating_value(&self, changes: &[i32]) -> Option<i32> {
+        if !Frequency::repeats(changes) {
+            return None;
+        }
+
         let mut seen = HashMap::new();
         let mut freq = Frequency(self.value());
 
@@ -51,7 +67,7 @@ impl Frequency {
 
                 let count = seen.entry(value).or_insert(1);
                 if *count == 2 {
-                    return value;
+                    return Some(value);
                 }
                 *count += 1;
 
@@ -59,33 +75,62 @@ impl Frequency {
             }
         }
     }
-}
 
-fn changes_from_file(input: &str) -> Vec<i32> {
-    let f = File::open(input).expect("file not found");
-    let file = BufReader::new(&f);
-
-    file.lines()
-        .map(|line| {
-            let change = line.expect("could not read line");
-            change
-                .parse::<i32>()
-                .expect(&format!("could not parse change: {}", change))
-        })
-        .collect()
+    // Every pass over the changes shifts the frequencies of the first one by
+    // their total, so one repeats only if the total is zero or two of the
+    // first pass are congruent modulo it.
+    fn repeats(changes: &[i32]) -> bool {
+        let total: i64 = changes.iter().map(|&c| i64::from(c)).sum();
+        if total == 0 {
+            return !changes.is_empty();
+        }
+
+        let mut seen = HashSet::new();
+        let mut freq = 0i64;
+        for &c in changes {
+            if !seen.insert(freq.rem_euclid(total.abs())) {
+                return true;
+            }
+            freq += i64::from(c);
+        }
+
+        false
+    }
 }
 
-fn day1_part2(changes: &[i32]) {
-    let result = Frequency::new().first_repeating_value(changes);
-    println!("First repeating value: {}", result);
+/// Parses one frequency change per line.
+pub fn parse_changes(input: &str) -> Result<Vec<i32>> {
+    error::parse_lines(input, |change| {
+        let offset = change.len() - change.trim_start().len();
+        error::parse_field(change, offset, change.trim())
+    })
 }
 
-pub fn day1(input: &str) {
-    let changes = changes_from_file(input);
-    let Frequency(num) = Frequency::new().add_changes(&changes);
-    println!("Final frequency: {}", num);
+/// Chronal Calibration.
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
 
-    day1_part2(&changes);
+    fn parse(&self, input: &str) -> Result<Vec<i32>> {
+        parse_changes(input)
+    }
+
+    fn part1(&self, changes: &Vec<i32>) -> Result<i32> {
+        Ok(Frequency::new().add_changes(changes).value())
+    }
+
+    fn part2(&self, changes: &Vec<i32>) -> Result<i32> {
+        if changes.is_empty() {
+            return Err(Error::invalid("No frequency changes to repeat"));
+        }
+
+        Frequency::new()
+            .first_repeating_value(changes)
+            .ok_or_else(|| Error::invalid("No frequency is ever reached twice"))
+    }
 }
 
 #[test]
@@ -134,17 +179,29 @@ fn test_change_frequency_from_strs() {
 
 #[test]
 fn test_first_repeating_value() {
-    assert_eq!(Frequency::new().first_repeating_value(&vec!(1, -1)), 0);
+    assert_eq!(
+        Frequency::new().first_repeating_value(&vec!(1, -1)),
+        Some(0)
+    );
     assert_eq!(
         Frequency::new().first_repeating_value(&vec!(3, 3, 4, -2, -4)),
-        10
+        Some(10)
     );
     assert_eq!(
         Frequency::new().first_repeating_value(&vec!(-6, 3, 8, 5, -6)),
-        5
+        Some(5)
     );
     assert_eq!(
         Frequency::new().first_repeating_value(&vec!(7, 7, -2, -7, -4)),
-        14
+        Some(14)
     );
+    assert_eq!(Frequency::new().first_repeating_value(&[1]), None);
+    assert_eq!(Frequency::new().first_repeating_value(&[3, 5, -2]), None);
+    assert_eq!(Frequency::new().first_repeating_value(&[]), None);
+}
+
+#[test]
+fn test_part2_without_repeat() {
+    assert!(Day1.solve("+1").is_err());
+    assert!(Day1.solve("+3\n+5\n-2").is_err());
 }
@@ -1,10 +1,12 @@
 // https://adventofcode.com/2018/day/2
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
-type Counts = (i32, i32);
+use error::{Error, Result};
+use solution::Solution;
+
+/// Whether an id has a letter exactly twice and exactly three times, as `0` or `1`.
+pub type Counts = (i32, i32);
 
 fn frequency_map(id: &str) -> HashMap<char, i32> {
     let mut fmap = HashMap::new();
@@ -17,7 +19,8 @@ fn frequency_map(id: &str) -> HashMap<char, i32> {
     fmap
 }
 
-fn get_counts(id: &str) -> Counts {
+/// Counts letters repeated exactly twice and three times in a box id.
+pub fn get_counts(id: &str) -> Counts {
     let fmap = frequency_map(id);
     let mut twos = false;
     let mut threes = false;
@@ -37,7 +40,8 @@ fn get_counts(id: &str) -> Counts {
     res
 }
 
-fn checksum(ids: &[String]) -> i32 {
+/// Number of ids with a doubled letter times the number with a tripled letter.
+pub fn checksum(ids: &[String]) -> i32 {
     let (sum2, sum3) = ids
         .iter()
         .map(|x| get_counts(x))
@@ -46,37 +50,39 @@ fn checksum(ids: &[String]) -> i32 {
     sum2 * sum3
 }
 
-pub fn day2(input: &str) {
-    let f = File::open(input).expect("file not found");
-    let file = BufReader::new(&f);
+/// Inventory Management System.
+pub struct Day2;
 
-    let ids: Vec<String> = file
-        .lines()
-        .map(|line| line.expect("fail to read input line").to_string())
-        .collect();
+impl Solution for Day2 {
+    type Input = Vec<String>;
+    type Part1 = i32;
+    type Part2 = String;
 
-    let csum = checksum(&ids);
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.lines().map(|line| line.to_string()).collect())
+    }
 
-    println!("Checksum: {}", csum);
-    day2_part2(&ids);
-}
+    fn part1(&self, ids: &Vec<String>) -> Result<i32> {
+        Ok(checksum(ids))
+    }
 
-fn day2_part2(ids: &[String]) {
-    match common_letters_in_ids(ids) {
-        Some(letters) => println!("Common letters are: {}", letters),
-        None => println!("No common letters found!"),
+    fn part2(&self, ids: &Vec<String>) -> Result<String> {
+        common_letters_in_ids(ids)
+            .ok_or_else(|| Error::invalid("No two box ids differ by exactly one character"))
     }
 }
 
-fn distance(left: &str, right: &str) -> i32 {
+/// Number of positions at which the two ids differ.
+pub fn distance(left: &str, right: &str) -> i32 {
     left.chars()
         .zip(right.chars())
         .map(|(x, y)| if x == y { 0 } else { 1 })
         .fold(0, |acc, x| acc + x)
 }
 
-fn correct_boxes(ids: &[String]) -> Option<(String, String)> {
-    for i in 0..ids.len() - 1 {
+/// First pair of ids that differ by exactly one character.
+pub fn correct_boxes(ids: &[String]) -> Option<(String, String)> {
+    for i in 0..ids.len() {
         for j in i + 1..ids.len() {
             let left = &ids[i];
             let right = &ids[j];
@@ -102,7 +108,8 @@ fn same_letters(left: &str, right: &str) -> String {
     same
 }
 
-fn common_letters_in_ids(ids: &[String]) -> Option<String> {
+/// Letters shared by the two correct box ids.
+pub fn common_letters_in_ids(ids: &[String]) -> Option<String> {
     match correct_boxes(ids) {
         Some((left, right)) => Some(same_letters(&left, &right)),
         None => None,
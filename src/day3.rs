@@ -2,21 +2,22 @@
 
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
-type Result<T> = ::std::result::Result<T, Box<::std::error::Error + Send + Sync>>;
+use error::{self, Error, Result};
+use solution::Solution;
 
+/// Rectangle of fabric in square inches from the top left edge.
 #[derive(Debug, PartialEq, Hash, Eq, Clone)]
-struct Sheet {
-    left: i32,
-    top: i32,
-    width: i32,
-    height: i32,
+pub struct Sheet {
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
 }
 
 impl Sheet {
-    fn from(pos: (i32, i32), dim: (i32, i32)) -> Sheet {
+    /// Sheet at `(left, top)` with `(width, height)`.
+    pub fn from(pos: (i32, i32), dim: (i32, i32)) -> Sheet {
         let (left, top) = pos;
         let (width, height) = dim;
         Sheet {
@@ -28,25 +29,35 @@ impl Sheet {
     }
 }
 
+/// An elf's claim on a sheet of fabric.
 #[derive(Debug, PartialEq, Clone)]
-struct Claim {
-    id: i32,
-    sheet: Sheet,
+pub struct Claim {
+    pub id: i32,
+    pub sheet: Sheet,
 }
 
 impl Claim {
-    fn parse_claim(cstr: &str) -> Result<Claim> {
-        let re = Regex::new(
-            r"^#(?P<id>\d+) @ (?P<left>\d+),(?P<top>\d+): (?P<width>\d+)x(?P<height>\d+)$",
-        )?;
+    /// Parses a claim like `#123 @ 3,2: 5x4`.
+    pub fn parse_claim(cstr: &str) -> Result<Claim> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^#(?P<id>\d+) @ (?P<left>\d+),(?P<top>\d+): (?P<width>\d+)x(?P<height>\d+)$",
+            )
+            .unwrap();
+        }
 
-        match re.captures(cstr) {
+        match RE.captures(cstr) {
             Some(caps) => {
-                let id = caps["id"].parse::<i32>()?;
-                let left = caps["left"].parse::<i32>()?;
-                let top = caps["top"].parse::<i32>()?;
-                let width = caps["width"].parse::<i32>()?;
-                let height = caps["height"].parse::<i32>()?;
+                let field = |name: &str| -> Result<i32> {
+                    let m = caps.name(name).unwrap();
+                    error::parse_field(cstr, m.start(), m.as_str())
+                };
+
+                let id = field("id")?;
+                let left = field("left")?;
+                let top = field("top")?;
+                let width = field("width")?;
+                let height = field("height")?;
 
                 let claim = Claim {
                     id,
@@ -60,12 +71,18 @@ impl Claim {
 
                 Ok(claim)
             }
-            None => Err(From::from(format!("Failed to parse claim from: {}", cstr))),
+            None => Err(Error::parse(
+                1,
+                1,
+                cstr,
+                "expected a claim like #123 @ 3,2: 5x4",
+            )),
         }
     }
 }
 
-fn get_coords_map(claims: &Vec<Claim>) -> HashMap<(i32, i32), i32> {
+/// Number of claims covering each square inch.
+pub fn get_coords_map(claims: &[Claim]) -> HashMap<(i32, i32), i32> {
     let mut coords: HashMap<(i32, i32), i32> = HashMap::new();
 
     // for each claim update the coords to keep track of used positions
@@ -83,7 +100,8 @@ fn get_coords_map(claims: &Vec<Claim>) -> HashMap<(i32, i32), i32> {
     coords
 }
 
-fn overlapping_area(coords_map: &HashMap<(i32, i32), i32>) -> i32 {
+/// Square inches covered by two or more claims.
+pub fn overlapping_area(coords_map: &HashMap<(i32, i32), i32>) -> i32 {
     // We only need to count posistions which are used more than once (overlapping positions)
     coords_map
         .values()
@@ -91,9 +109,9 @@ fn overlapping_area(coords_map: &HashMap<(i32, i32), i32>) -> i32 {
         .fold(0, |sum, c| sum + c)
 }
 
-// Will find the first non-overlapping Claim
-fn find_non_overlapping(
-    claims: &Vec<Claim>,
+/// Finds the first claim that does not overlap any other.
+pub fn find_non_overlapping(
+    claims: &[Claim],
     coords_map: &HashMap<(i32, i32), i32>,
 ) -> Option<Claim> {
     for c in claims {
@@ -120,27 +138,29 @@ fn find_non_overlapping(
     None
 }
 
-pub fn day3(input: &str) {
-    let f = File::open(input).expect("file not found");
-    let file = BufReader::new(&f);
-    let mut claims: Vec<Claim> = vec![];
-
-    for line in file.lines() {
-        let cstr = line.expect("failed to read input line");
-        if let Ok(claim) = Claim::parse_claim(&cstr) {
-            claims.push(claim);
-        } else {
-            println!("Failed to parse line: {}", cstr);
-        }
+/// No Matter How You Slice It.
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<Claim>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, input: &str) -> Result<Vec<Claim>> {
+        error::parse_lines(input, Claim::parse_claim)
     }
 
-    let coords_map = get_coords_map(&claims);
-    let area = overlapping_area(&coords_map);
-    println!("Overlapping area: {}", area);
+    fn part1(&self, claims: &Vec<Claim>) -> Result<i32> {
+        let coords_map = get_coords_map(claims);
+        Ok(overlapping_area(&coords_map))
+    }
 
-    // Part 2
-    let result = find_non_overlapping(&claims, &coords_map);
-    println!("Non overlapping claim: {:?}", result);
+    fn part2(&self, claims: &Vec<Claim>) -> Result<i32> {
+        let coords_map = get_coords_map(claims);
+        find_non_overlapping(claims, &coords_map)
+            .map(|c| c.id)
+            .ok_or_else(|| Error::invalid("Every claim overlaps another one"))
+    }
 }
 
 #[test]
@@ -192,3 +212,21 @@ fn test_parse_claim() {
         },
     );
 }
+
+#[test]
+fn test_parse_claim_errors() {
+    let err = Day3
+        .parse("#1 @ 1,3: 4x4\n#2 @ 3,99999999999: 4x4\n")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 2, column 8: invalid value \"99999999999\": number too large to fit in target type: \
+         \"#2 @ 3,99999999999: 4x4\""
+    );
+
+    let err = Day3.parse("#1 @ 1,3 4x4").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 1, column 1: expected a claim like #123 @ 3,2: 5x4: \"#1 @ 1,3 4x4\""
+    );
+}
@@ -1,35 +1,36 @@
 // To find most minutes sleep guard is just accumulation of minutes over the given records
 // But is it just that?
 
-use regex::Regex;
+use regex::{Captures, Regex};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
 use chrono::prelude::*;
 
-type Result<T> = ::std::result::Result<T, Box<::std::error::Error + Send + Sync>>;
+use error::{self, Error, Result};
+use solution::Solution;
 
+/// What was observed about the guard on duty.
 #[derive(Debug, PartialEq)]
-enum Action {
+pub enum Action {
     Shift { guard: i32 },
     Sleep,
     WakeUp,
 }
 
+/// A timestamped line from the guard log.
 #[derive(Debug, PartialEq)]
-struct Record {
-    time: DateTime<Utc>,
-    observation: Action,
+pub struct Record {
+    pub time: DateTime<Utc>,
+    pub observation: Action,
 }
 
 // What do we need to keep stats of about the guards?
 // 1. We need to keep the total time they sleep
 // 2. Then we also need to know which minute of the hour they are most likely to fall asleep
 #[derive(Debug)]
-struct Stats {
-    sleep_total: i32,
-    sleep_times: HashMap<i32, i32>,
+pub struct Stats {
+    pub sleep_total: i32,
+    pub sleep_times: HashMap<i32, i32>,
 }
 
 impl Stats {
@@ -50,14 +51,23 @@ impl Stats {
         }
     }
 
-    // Returns the most frequently slept minute and the times it was found to be
-    fn get_most_frequent(&self) -> Option<(&i32, &i32)> {
+    /// Returns the most frequently slept minute and the times it was found to be
+    pub fn get_most_frequent(&self) -> Option<(&i32, &i32)> {
         self.sleep_times.iter().max_by(|a, b| a.1.cmp(b.1))
     }
 }
 
+fn parse_date(srecord: &str, caps: &Captures) -> Result<DateTime<Utc>> {
+    let m = caps.name("date").unwrap();
+
+    Utc.datetime_from_str(m.as_str(), "%Y-%m-%d %H:%M")
+        .map_err(|e| Error::parse(1, m.start() + 1, srecord, format!("invalid date: {}", e)))
+}
+
 impl Record {
-    fn parse(srecord: &str) -> Result<Record> {
+    /// Parses a shift, sleep or wake up line such as
+    /// `[1518-11-01 00:05] falls asleep`.
+    pub fn parse(srecord: &str) -> Result<Record> {
         if srecord.ends_with("begins shift") {
             Self::parse_shift_record(srecord)
         } else if srecord.ends_with("falls asleep") {
@@ -65,7 +75,12 @@ impl Record {
         } else if srecord.ends_with("wakes up") {
             Self::parse_wakeup_record(srecord)
         } else {
-            Err(From::from(format!("Failed to parse record: {}", srecord)))
+            Err(Error::parse(
+                1,
+                1,
+                srecord,
+                "expected a shift, falls asleep or wakes up record",
+            ))
         }
     }
 
@@ -77,8 +92,9 @@ impl Record {
 
         match RE.captures(srecord) {
             Some(caps) => {
-                let date = Utc.datetime_from_str(&caps["date"], "%Y-%m-%d %H:%M")?;
-                let id = caps["id"].parse::<i32>()?;
+                let date = parse_date(srecord, &caps)?;
+                let id = caps.name("id").unwrap();
+                let id = error::parse_field(srecord, id.start(), id.as_str())?;
 
                 let record = Record {
                     time: date,
@@ -87,7 +103,7 @@ impl Record {
 
                 Ok(record)
             }
-            None => Err(From::from(format!("Not a valid shift record: {}", srecord))),
+            None => Err(Error::parse(1, 1, srecord, "not a valid shift record")),
         }
     }
 
@@ -98,7 +114,7 @@ impl Record {
 
         match RE.captures(srecord) {
             Some(caps) => {
-                let date = Utc.datetime_from_str(&caps["date"], "%Y-%m-%d %H:%M")?;
+                let date = parse_date(srecord, &caps)?;
 
                 let record = Record {
                     time: date,
@@ -107,7 +123,7 @@ impl Record {
 
                 Ok(record)
             }
-            None => Err(From::from(format!("Not a valid sleep record: {}", srecord))),
+            None => Err(Error::parse(1, 1, srecord, "not a valid sleep record")),
         }
     }
 
@@ -118,7 +134,7 @@ impl Record {
 
         match RE.captures(srecord) {
             Some(caps) => {
-                let date = Utc.datetime_from_str(&caps["date"], "%Y-%m-%d %H:%M")?;
+                let date = parse_date(srecord, &caps)?;
 
                 let record = Record {
                     time: date,
@@ -127,19 +143,17 @@ impl Record {
 
                 Ok(record)
             }
-            None => Err(From::from(format!(
-                "Not a valid wake up record: {}",
-                srecord
-            ))),
+            None => Err(Error::parse(1, 1, srecord, "not a valid wake up record")),
         }
     }
 
-    // Sort records by the timestamp in the ascending order
-    fn sort_records(rs: &mut Vec<Record>) {
+    /// Sort records by the timestamp in the ascending order
+    pub fn sort_records(rs: &mut [Record]) {
         rs.sort_by(|a, b| a.time.cmp(&b.time));
     }
 
-    fn process(records: &Vec<Record>) -> HashMap<i32, Stats> {
+    /// Sleep stats per guard id from records sorted by time.
+    pub fn process(records: &[Record]) -> Result<HashMap<i32, Stats>> {
         // Go through the records and process the sleep times of Guards
         let mut current_guard = None;
         let mut sleep_time = None;
@@ -151,79 +165,87 @@ impl Record {
                 Action::Shift { guard } => current_guard = Some(guard),
                 Action::Sleep => sleep_time = Some(r.time),
                 Action::WakeUp => {
-                    if let Some(st) = sleep_time {
-                        let duration = (r.time.minute() - st.minute()) as i32;
-                        let id =
-                            current_guard.expect("Invalid record - no guard to record time for");
-                        let stat = stats.entry(id).or_insert(Stats::empty());
-                        stat.record(st, duration);
-                    } else {
-                        println!("Error - sleep time not recorded!");
+                    let st = sleep_time.take().ok_or_else(|| {
+                        Error::invalid(format!(
+                            "Guard wakes up at {} without falling asleep",
+                            r.time
+                        ))
+                    })?;
+                    let id = current_guard.ok_or_else(|| {
+                        Error::invalid(format!("No guard on shift to wake up at {}", r.time))
+                    })?;
+                    if r.time.minute() < st.minute() {
+                        return Err(Error::invalid(format!(
+                            "Guard {} wakes up at {} before falling asleep at {}",
+                            id, r.time, st
+                        )));
                     }
+
+                    let duration = (r.time.minute() - st.minute()) as i32;
+                    let stat = stats.entry(id).or_insert(Stats::empty());
+                    stat.record(st, duration);
                 }
             }
         }
 
-        stats
+        Ok(stats)
     }
 
-    fn find_best_by_total(stats: &HashMap<i32, Stats>) {
+    /// Strategy 1: the sleepiest guard id times their sleepiest minute.
+    pub fn find_best_by_total(stats: &HashMap<i32, Stats>) -> Result<i32> {
         // We need to find who sleeps the most
-        let m = stats
+        let (guard, stat_for_guard) = stats
             .iter()
-            .map(|(id, st)| (id, st.sleep_total))
-            .max_by(|x, y| x.1.cmp(&y.1));
+            .max_by(|x, y| x.1.sleep_total.cmp(&y.1.sleep_total))
+            .ok_or_else(|| Error::invalid("Could not find the guard who sleeps most"))?;
 
-        if let Some((guard, total)) = m {
-            println!("Guard {} sleeps most with total {} minutes.", guard, total);
+        // Then we need to find the minute they like to sleep the most
+        let (frequent_min, _) = stat_for_guard
+            .get_most_frequent()
+            .ok_or_else(|| Error::invalid(format!("Guard {} never sleeps", guard)))?;
 
-            // Then we need to find the minute they like to sleep the most
-            let stat_for_guard = stats.get(guard).unwrap();
-            let (frequent_min, _) = stat_for_guard.get_most_frequent().unwrap();
-            println!("Most frequent minute: {:?}", frequent_min);
-
-            let answer = frequent_min * guard;
-            println!("Best by total number of minutes: {}", answer);
-        } else {
-            println!("Error: could not find the guard who sleeps most!");
-        }
+        Ok(frequent_min * guard)
     }
 
-    fn find_best_by_most(stats: &HashMap<i32, Stats>) {
+    /// Strategy 2: id of the guard most often asleep on the same minute times
+    /// that minute.
+    pub fn find_best_by_most(stats: &HashMap<i32, Stats>) -> Result<i32> {
         let max_by_minute_times = stats
             .iter()
-            .map(|(id, s)| {
-                let (minute, times) = s.get_most_frequent().unwrap();
-                (id, times, minute)
+            .filter_map(|(id, s)| {
+                s.get_most_frequent()
+                    .map(|(minute, times)| (id, times, minute))
             })
-            .max_by(|a, b| a.1.cmp(&b.1));
+            .max_by(|a, b| a.1.cmp(b.1));
 
-        let (guard, _, minute) = max_by_minute_times.unwrap();
-        let answer = guard * minute;
-        println!("Best by most minutes: {}", answer);
+        let (guard, _, minute) =
+            max_by_minute_times.ok_or_else(|| Error::invalid("No guard was ever asleep"))?;
+        Ok(guard * minute)
     }
 }
 
-pub fn day4(input: &str) {
-    let f = File::open(input).expect("file not found");
-    let file = BufReader::new(&f);
+/// Repose Record.
+pub struct Day4;
 
-    let mut records = vec![];
+impl Solution for Day4 {
+    type Input = HashMap<i32, Stats>;
+    type Part1 = i32;
+    type Part2 = i32;
 
-    for line in file.lines() {
-        let srecord = line.expect("failed to read input line");
+    fn parse(&self, input: &str) -> Result<HashMap<i32, Stats>> {
+        let mut records = error::parse_lines(input, Record::parse)?;
 
-        if let Ok(record) = Record::parse(&srecord) {
-            records.push(record);
-        } else {
-            println!("Failed to parse line: {}", srecord);
-        }
+        Record::sort_records(&mut records);
+        Record::process(&records)
+    }
+
+    fn part1(&self, stats: &HashMap<i32, Stats>) -> Result<i32> {
+        Record::find_best_by_total(stats)
     }
 
-    Record::sort_records(&mut records);
-    let records = Record::process(&records);
-    Record::find_best_by_total(&records);
-    Record::find_best_by_most(&records);
+    fn part2(&self, stats: &HashMap<i32, Stats>) -> Result<i32> {
+        Record::find_best_by_most(stats)
+    }
 }
 
 #[test]
@@ -267,3 +289,22 @@ fn test_parse_record() {
     assert_eq!(record.time.day(), 30);
     assert_eq!(record.observation, Action::WakeUp);
 }
+
+#[test]
+fn test_process_errors() {
+    let err = Day4
+        .parse("[1518-11-01 00:05] falls asleep\n[1518-11-01 00:25] wakes up")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "No guard on shift to wake up at 1518-11-01 00:25:00 UTC"
+    );
+
+    let err = Day4
+        .parse("[1518-11-01 00:00] Guard #10 begins shift\n[1518-13-01 00:05] falls asleep")
+        .unwrap_err();
+    assert_eq!(err.exit_code(), 65);
+    assert!(err
+        .to_string()
+        .starts_with("line 2, column 2: invalid date: "));
+}
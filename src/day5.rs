@@ -1,13 +1,15 @@
 // https://adventofcode.com/2018/day/5
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use error::{Error, Result};
+use solution::Solution;
 
-fn reacts(x: char, y: char) -> bool {
+/// Whether two units are the same type with opposite polarity.
+pub fn reacts(x: char, y: char) -> bool {
     (x != y) && (x.to_lowercase().to_string() == y.to_lowercase().to_string())
 }
 
-fn polymer_react(ps: &str) -> String {
+/// Polymer left after every reaction has happened.
+pub fn polymer_react(ps: &str) -> String {
     let mut poly: Vec<char> = vec![];
 
     for c in ps.chars() {
@@ -26,30 +28,38 @@ fn polymer_react(ps: &str) -> String {
     poly.iter().collect()
 }
 
-pub fn day5(input: &str) {
-    let f = File::open(input).expect("Failed to open input file");
-    let mut reader = BufReader::new(f);
+/// Alchemical Reduction.
+pub struct Day5;
 
-    let mut line = String::new();
-    reader.read_line(&mut line).expect("Failed to read buffer");
+impl Solution for Day5 {
+    type Input = String;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let polymer = line.trim();
-    println!("Polymer1 length: {}", polymer_react(polymer).len());
+    fn parse(&self, input: &str) -> Result<String> {
+        Ok(input.lines().next().unwrap_or("").trim().to_string())
+    }
+
+    fn part1(&self, polymer: &String) -> Result<usize> {
+        Ok(polymer_react(polymer).len())
+    }
 
-    if let Some(shortest) = shortest_polymer(polymer) {
-        println!("Shortest polymer length: {}", shortest.len());
-    } else {
-        println!("Couldn't find the shortest polymer");
+    fn part2(&self, polymer: &String) -> Result<usize> {
+        shortest_polymer(polymer)
+            .map(|p| p.len())
+            .ok_or_else(|| Error::invalid("Couldn't find the shortest polymer"))
     }
 }
 
-fn filter_unit(ps: &str, u: char) -> String {
+/// Removes all units of type `u`, regardless of polarity.
+pub fn filter_unit(ps: &str, u: char) -> String {
     ps.chars()
         .filter(|&c| c.to_lowercase().to_string() != u.to_lowercase().to_string())
         .collect()
 }
 
-fn shortest_polymer(ps: &str) -> Option<String> {
+/// Shortest fully reacted polymer after removing a single unit type.
+pub fn shortest_polymer(ps: &str) -> Option<String> {
     ('a' as u8..('z' as u8 + 1))
         .map(|u| polymer_react(&filter_unit(ps, u as char)))
         .min_by_key(|x| x.len())
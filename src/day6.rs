@@ -1,16 +1,20 @@
 use std::cmp::{Ord, Ordering};
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::iter::Iterator;
 
-type Point = (i32, i32);
+use error::{self, Error, Result};
+use solution::Solution;
 
-fn manhatten_distance(x: Point, y: Point) -> i32 {
+/// Grid coordinate as `(x, y)`.
+pub type Point = (i32, i32);
+
+/// Taxicab distance between two points.
+pub fn manhatten_distance(x: Point, y: Point) -> i32 {
     (x.0 - y.0).abs() + (x.1 - y.1).abs()
 }
 
-fn find_closest(p: Point, points: &Vec<Point>) -> Option<Point> {
+/// The single coordinate closest to `p`, or `None` on a tie.
+pub fn find_closest(p: Point, points: &[Point]) -> Option<Point> {
     unique_extreme_by_iter(
         &mut points.iter().map(|&s| (s, manhatten_distance(p, s))),
         |x, y| x.1.cmp(&y.1),
@@ -47,14 +51,16 @@ where
     umin
 }
 
-struct Bounds {
-    min_x: i32,
-    max_x: i32,
-    min_y: i32,
-    max_y: i32,
+/// Bounding box of a set of points.
+pub struct Bounds {
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
 }
 
-fn score_around(points: &Vec<Point>, bounds: &Bounds, out: i32) -> HashMap<Point, i32> {
+/// Size of the area closest to each point within `bounds` grown by `out`.
+pub fn score_around(points: &[Point], bounds: &Bounds, out: i32) -> HashMap<Point, i32> {
     let mut point_scores = HashMap::new();
     for i in bounds.min_x - out..bounds.max_x + out + 1 {
         for j in bounds.min_y - out..bounds.max_y + out + 1 {
@@ -70,14 +76,15 @@ fn score_around(points: &Vec<Point>, bounds: &Bounds, out: i32) -> HashMap<Point
     point_scores
 }
 
-fn total_distance_to_point(p: Point, points: &Vec<Point>) -> i32 {
+fn total_distance_to_point(p: Point, points: &[Point]) -> i32 {
     points
         .iter()
         .fold(0, |acc, &t| acc + manhatten_distance(p, t))
 }
 
-fn points_around_within_distance(
-    points: &Vec<Point>,
+/// Locations whose total distance to every point is less than `distance`.
+pub fn points_around_within_distance(
+    points: &[Point],
     bounds: &Bounds,
     distance: i32,
     around: i32,
@@ -97,7 +104,8 @@ fn points_around_within_distance(
     region
 }
 
-fn process_distance(points: &Vec<Point>, bounds: &Bounds) -> Option<i32> {
+/// Size of the largest area that isn't infinite.
+pub fn process_distance(points: &[Point], bounds: &Bounds) -> Option<i32> {
     let s1 = score_around(points, bounds, 400);
     let s2 = score_around(points, bounds, 600);
 
@@ -116,7 +124,25 @@ fn process_distance(points: &Vec<Point>, bounds: &Bounds) -> Option<i32> {
     bounded.last().map(|k| k.1)
 }
 
-fn bounds_of(points: &Vec<Point>) -> Bounds {
+/// Parses a coordinate written as `x, y`.
+pub fn parse_point(line: &str) -> Result<Point> {
+    let comma = line
+        .find(',')
+        .ok_or_else(|| Error::parse(1, 1, line, "expected a coordinate like 1, 6"))?;
+    let (sx, sy) = (&line[..comma], &line[comma + 1..]);
+
+    let x = error::parse_field(line, sx.len() - sx.trim_start().len(), sx.trim())?;
+    let y = error::parse_field(
+        line,
+        comma + 1 + sy.len() - sy.trim_start().len(),
+        sy.trim(),
+    )?;
+
+    Ok((x, y))
+}
+
+/// Panics if `points` is empty.
+pub fn bounds_of(points: &[Point]) -> Bounds {
     let min_x: i32 = points
         .iter()
         .map(|x| x.0)
@@ -147,30 +173,34 @@ fn bounds_of(points: &Vec<Point>) -> Bounds {
     }
 }
 
-pub fn day6(input: &str) {
-    let f = File::open(input).expect("Failed to open input file");
-    let reader = BufReader::new(f);
-    let mut points = vec![];
-
-    for line in reader.lines() {
-        let spoint = line.expect("Failed to read line");
-        let parts: Vec<&str> = spoint.split(",").collect();
-        if parts.len() == 2 {
-            let x = parts[0].trim().parse().unwrap();
-            let y = parts[1].trim().parse().unwrap();
-            points.push((x, y));
-        }
-    }
+/// Chronal Coordinates.
+pub struct Day6;
 
-    let bounds = bounds_of(&points);
+impl Solution for Day6 {
+    type Input = Vec<Point>;
+    type Part1 = i32;
+    type Part2 = usize;
 
-    let max_bounded = process_distance(&points, &bounds);
-    println!("Max bounded: {:?}", max_bounded);
+    fn parse(&self, input: &str) -> Result<Vec<Point>> {
+        let points = error::parse_lines(input, parse_point)?;
 
-    let distance = 10000;
-    let region_within = points_around_within_distance(&points, &bounds, distance, 400);
+        if points.is_empty() {
+            return Err(Error::invalid("No coordinates in input"));
+        }
+
+        Ok(points)
+    }
 
-    println!("Bounded region contains {} points", region_within.len());
+    fn part1(&self, points: &Vec<Point>) -> Result<i32> {
+        let bounds = bounds_of(points);
+        process_distance(points, &bounds).ok_or_else(|| Error::invalid("No bounded area found"))
+    }
+
+    fn part2(&self, points: &Vec<Point>) -> Result<usize> {
+        let bounds = bounds_of(points);
+        let distance = 10000;
+        Ok(points_around_within_distance(points, &bounds, distance, 400).len())
+    }
 }
 
 #[test]
@@ -1,22 +1,32 @@
 use regex::Regex;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::fmt;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
-type Result<T> = ::std::result::Result<T, Box<::std::error::Error + Send + Sync>>;
+use error::{self, Error, Result};
+use solution::Solution;
+use summary::table;
 
-type TaskName = char;
+/// Any label without surrounding whitespace, e.g. `C` or `build-core`.
+pub type TaskName = String;
 
+/// Workers available for part 2.
+pub const WORKERS: usize = 5;
+
+/// Seconds every task takes on top of its `task_time` in part 2.
+pub const WORK_TIME: i32 = 60;
+
+/// Tasks and the set of tasks each one is still waiting on.
 #[derive(Debug, Clone)]
-struct TaskMap {
+pub struct TaskMap {
     tmap: HashMap<TaskName, HashSet<TaskName>>,
 }
 
+/// `task` cannot begin until `depends_on` is finished.
 #[derive(PartialEq)]
-struct Dependency {
-    task: TaskName,
-    depends_on: TaskName,
+pub struct Dependency {
+    pub task: TaskName,
+    pub depends_on: TaskName,
 }
 
 impl fmt::Debug for Dependency {
@@ -26,64 +36,203 @@ impl fmt::Debug for Dependency {
 }
 
 impl Dependency {
-    fn new(task: TaskName, depends_on: TaskName) -> Self {
-        Dependency { task, depends_on }
+    /// `task` can only begin once `depends_on` is finished.
+    pub fn new<T: Into<TaskName>, D: Into<TaskName>>(task: T, depends_on: D) -> Self {
+        Dependency {
+            task: task.into(),
+            depends_on: depends_on.into(),
+        }
     }
 
-    fn parse(line: &str) -> Result<Dependency> {
-        // Step C must be finished before step A can begin.
+    /// Parses `Step C must be finished before step A can begin.`, where the
+    /// steps can be any labels, e.g. `Step fetch deps must be finished
+    /// before step build can begin.`
+    pub fn parse(line: &str) -> Result<Dependency> {
         lazy_static! {
             static ref RE: Regex = Regex::new(
-                r"Step (?P<parent>[A-Z]) must be finished before step (?P<task>[A-Z]) can begin\."
+                r"^\s*Step (?P<parent>\S.*?) must be finished before step (?P<task>\S.*?) can begin\.\s*$"
             )
             .unwrap();
         }
 
         match RE.captures(line) {
             Some(caps) => {
-                let depends_on = caps["parent"].parse::<TaskName>()?;
-                let task = caps["task"].parse::<TaskName>()?;
-
-                let dep = Dependency { task, depends_on };
+                let dep = Dependency::new(caps["task"].trim_end(), caps["parent"].trim_end());
 
                 Ok(dep)
             }
-            None => Err(From::from(format!("Not a task spec: {}", line))),
+            None => Err(Error::parse(
+                1,
+                1,
+                line,
+                "expected a step like Step C must be finished before step A can begin.",
+            )),
         }
     }
 }
 
+/// Tasks that can never be completed because some of them wait on each
+/// other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleError {
+    /// Tasks in the order they would have to be finished, ending with the
+    /// first one again
+    pub cycle: Vec<TaskName>,
+    /// Other tasks that can't run because they wait on the cycle
+    pub blocked: Vec<TaskName>,
+}
+
+/// Result of anything that needs the task graph to be free of cycles.
+pub type CycleResult<T> = ::std::result::Result<T, CycleError>;
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Dependency cycle: {}", self.cycle.join(" -> "))?;
+        if !self.blocked.is_empty() {
+            write!(f, " (also blocks {})", self.blocked.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<CycleError> for Error {
+    fn from(e: CycleError) -> Error {
+        Error::invalid(e.to_string())
+    }
+}
+
 impl TaskMap {
-    fn empty() -> Self {
+    /// Map without any tasks.
+    pub fn empty() -> Self {
         TaskMap {
             tmap: HashMap::new(),
         }
     }
 
-    fn is_empty(&self) -> bool {
+    /// Whether every task has been completed.
+    pub fn is_empty(&self) -> bool {
         self.tmap.is_empty()
     }
 
-    fn add(&mut self, dep: Dependency) {
+    /// Records a dependency, adding both tasks to the map.
+    pub fn add(&mut self, dep: Dependency) {
         {
             let v = self.tmap.entry(dep.task).or_default();
-            v.insert(dep.depends_on);
+            v.insert(dep.depends_on.clone());
         }
         {
             // Add an entry for depends_on as a task as well
-            self.tmap.entry(dep.depends_on).or_default();
+            self.tmap.entry(dep.depends_on.clone()).or_default();
+        }
+    }
+
+    /// Checks that every task can eventually be completed.
+    pub fn validate(&self) -> CycleResult<()> {
+        // Complete everything that can be, whatever is left is stuck
+        let mut stuck = self.clone();
+        loop {
+            let ts = stuck.completable_tasks();
+            if ts.is_empty() {
+                break;
+            }
+            for t in ts {
+                stuck.complete_task(&t);
+            }
         }
+
+        if stuck.is_empty() {
+            return Ok(());
+        }
+
+        // Every stuck task waits on another stuck one, so following them
+        // from any task has to come back around
+        let mut path = vec![];
+        let mut task = stuck.tmap.keys().min().unwrap().clone();
+        while !path.contains(&task) {
+            path.push(task.clone());
+            task = stuck.tmap[&task].iter().min().unwrap().clone();
+        }
+
+        let start = path.iter().position(|t| *t == task).unwrap();
+        let mut cycle = path.split_off(start);
+        cycle.push(task);
+        cycle.reverse();
+
+        let mut blocked: Vec<TaskName> = stuck
+            .tmap
+            .keys()
+            .filter(|&t| !cycle.contains(t))
+            .cloned()
+            .collect();
+        blocked.sort();
+
+        Err(CycleError { cycle, blocked })
     }
 
-    // TODO: Add logic to check whether it is actually completable or not
-    fn complete_task(&mut self, task: TaskName) -> bool {
-        if self.tmap.contains_key(&task) {
+    /// Graphviz DOT of the dependency graph, with an edge from each task to
+    /// the ones waiting on it. Given the `order` from `process_tasks`, nodes
+    /// are numbered and coloured by their completion step.
+    pub fn to_dot(&self, order: Option<&[TaskName]>) -> String {
+        let mut tasks: Vec<&TaskName> = self.tmap.keys().collect();
+        tasks.sort();
+
+        let mut out = String::from("digraph day7 {\n    rankdir=LR;\n    node [shape=box];\n");
+
+        for &t in &tasks {
+            let mut label = dot_escape(t);
+            if let Some(time) = task_time(t) {
+                label.push_str(&format!(" ({}s)", time));
+            }
+
+            let step = order.and_then(|o| o.iter().position(|s| s == t));
+            match step {
+                Some(step) => {
+                    // Hue from red for the first step to blue for the last
+                    let steps = order.map_or(1, |o| o.len()).max(2) - 1;
+                    let hue = 0.667 * step as f64 / steps as f64;
+                    out.push_str(&format!(
+                        "    \"{}\" [label=\"{}\\nstep {}\", style=filled, fillcolor=\"{:.3} 0.400 1.000\"];\n",
+                        dot_escape(t),
+                        label,
+                        step + 1,
+                        hue
+                    ));
+                }
+                None => out.push_str(&format!(
+                    "    \"{}\" [label=\"{}\"];\n",
+                    dot_escape(t),
+                    label
+                )),
+            }
+        }
+
+        for &t in &tasks {
+            let mut deps: Vec<&TaskName> = self.tmap[t].iter().collect();
+            deps.sort();
+            for d in deps {
+                out.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    dot_escape(d),
+                    dot_escape(t)
+                ));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Removes `task` and releases the tasks waiting on it. Returns `false` if
+    /// the task is unknown.
+    pub fn complete_task(&mut self, task: &str) -> bool {
+        if self.tmap.contains_key(task) {
             // Remove task entry from the tmap, and then remove task from the
             // dependency sets of other existing tasks
 
-            self.tmap.remove(&task);
+            self.tmap.remove(task);
             for ds in self.tmap.values_mut() {
-                ds.remove(&task);
+                ds.remove(task);
             }
 
             true
@@ -92,24 +241,43 @@ impl TaskMap {
         }
     }
 
-    fn completable_tasks(&self) -> Vec<TaskName> {
-        let mut ts = vec![];
+    /// Like `complete_task`, returning the tasks that have no outstanding
+    /// dependencies left because of it.
+    pub fn release(&mut self, task: &str) -> Vec<TaskName> {
+        if self.tmap.remove(task).is_none() {
+            return vec![];
+        }
 
-        for (&t, ds) in &self.tmap {
-            if ds.is_empty() {
-                ts.push(t);
+        let mut released = vec![];
+        for (t, ds) in &mut self.tmap {
+            if ds.remove(task) && ds.is_empty() {
+                released.push(t.clone());
             }
         }
 
+        released
+    }
+
+    /// Tasks waiting directly on `task`, sorted.
+    pub fn dependents(&self, task: &str) -> Vec<TaskName> {
+        let mut ts: Vec<TaskName> = self
+            .tmap
+            .iter()
+            .filter(|&(_, ds)| ds.contains(task))
+            .map(|(t, _)| t.clone())
+            .collect();
+        ts.sort();
+
         ts
     }
 
-    fn completable_tasks_excluding(&self, exluding: &HashSet<TaskName>) -> Vec<TaskName> {
+    /// Tasks with no outstanding dependencies, in no particular order.
+    pub fn completable_tasks(&self) -> Vec<TaskName> {
         let mut ts = vec![];
 
-        for (&t, ds) in &self.tmap {
-            if ds.is_empty() && !exluding.contains(&t) {
-                ts.push(t);
+        for (t, ds) in &self.tmap {
+            if ds.is_empty() {
+                ts.push(t.clone());
             }
         }
 
@@ -117,107 +285,564 @@ impl TaskMap {
     }
 }
 
-fn process_tasks(mut tasks: TaskMap) -> Vec<TaskName> {
+/// Picks which of the available tasks to work on first.
+pub trait Policy {
+    /// `Less` if `a` should be worked on before `b`.
+    fn compare(&self, a: &str, b: &str) -> Ordering;
+}
+
+/// Alphabetically first task first, as in the puzzle.
+pub struct Alphabetical;
+
+impl Policy for Alphabetical {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Task taking the longest first, alphabetically on ties.
+pub struct LongestFirst<F> {
+    pub duration: F,
+}
+
+impl<F: Fn(&str) -> i32> Policy for LongestFirst<F> {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        (self.duration)(b)
+            .cmp(&(self.duration)(a))
+            .then_with(|| a.cmp(b))
+    }
+}
+
+/// Task with the most tasks waiting directly on it first, alphabetically on
+/// ties.
+pub struct MostDependentsFirst {
+    dependents: HashMap<TaskName, usize>,
+}
+
+impl MostDependentsFirst {
+    /// Counts the dependents of every task in `tasks`.
+    pub fn new(tasks: &TaskMap) -> Self {
+        let dependents = tasks
+            .tmap
+            .keys()
+            .map(|t| (t.clone(), tasks.dependents(t).len()))
+            .collect();
+
+        MostDependentsFirst { dependents }
+    }
+}
+
+impl Policy for MostDependentsFirst {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let count = |t: &str| self.dependents.get(t).cloned().unwrap_or(0);
+
+        count(b).cmp(&count(a)).then_with(|| a.cmp(b))
+    }
+}
+
+/// Any comparator works as a custom policy.
+impl<F: Fn(&str, &str) -> Ordering> Policy for F {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        self(a, b)
+    }
+}
+
+// An available task, ordered so that the one the policy picks first is the
+// greatest and comes out of a `BinaryHeap` first
+struct Ready<'p, P: Policy + ?Sized + 'p> {
+    task: TaskName,
+    policy: &'p P,
+}
+
+impl<'p, P: Policy + ?Sized> Ord for Ready<'p, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.policy.compare(&other.task, &self.task)
+    }
+}
+
+impl<'p, P: Policy + ?Sized> PartialOrd for Ready<'p, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'p, P: Policy + ?Sized> PartialEq for Ready<'p, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'p, P: Policy + ?Sized> Eq for Ready<'p, P> {}
+
+type Queue<'p, P> = BinaryHeap<Ready<'p, P>>;
+
+fn queue_tasks<'p, P: Policy + ?Sized>(
+    queue: &mut Queue<'p, P>,
+    tasks: Vec<TaskName>,
+    policy: &'p P,
+) {
+    queue.extend(tasks.into_iter().map(|task| Ready { task, policy }));
+}
+
+/// Order in which a single worker completes the tasks, taking the
+/// alphabetically first available task each time.
+pub fn process_tasks(tasks: TaskMap) -> CycleResult<Vec<TaskName>> {
+    process_tasks_by(tasks, &Alphabetical)
+}
+
+/// Like `process_tasks`, taking the available task `policy` picks first.
+pub fn process_tasks_by<P: Policy + ?Sized>(
+    mut tasks: TaskMap,
+    policy: &P,
+) -> CycleResult<Vec<TaskName>> {
+    tasks.validate()?;
+
+    let mut queue = Queue::new();
+    queue_tasks(&mut queue, tasks.completable_tasks(), policy);
+
     let mut completion_list = vec![];
-    loop {
-        let mut ts = tasks.completable_tasks();
+    while let Some(Ready { task, .. }) = queue.pop() {
+        queue_tasks(&mut queue, tasks.release(&task), policy);
+        completion_list.push(task);
+    }
 
-        if ts.is_empty() {
-            break;
-        }
+    Ok(completion_list)
+}
 
-        ts.sort();
-        ts.reverse();
+// Finish times of the tasks being worked on, earliest first, and the worker
+// doing each
+type Events = BinaryHeap<Reverse<(i32, TaskName, usize)>>;
+
+// Hands the queued tasks to the lowest numbered idle workers at `time`.
+fn start_work<F: Fn(&str) -> i32, P: Policy + ?Sized>(
+    queue: &mut Queue<P>,
+    events: &mut Events,
+    idle: &mut BTreeSet<usize>,
+    schedule: &mut Schedule,
+    duration: &F,
+    time: i32,
+) {
+    while !idle.is_empty() {
+        let task = match queue.pop() {
+            Some(ready) => ready.task,
+            None => break,
+        };
+        let worker = *idle.iter().next().unwrap();
+        idle.remove(&worker);
+
+        let end = time + duration(&task);
+        events.push(Reverse((end, task.clone(), worker)));
+        schedule.tasks.push(Scheduled {
+            worker,
+            task,
+            start: time,
+            end,
+        });
+    }
+}
+
+/// A task as worked on by one of the workers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheduled {
+    /// Worker number, from 1
+    pub worker: usize,
+    pub task: TaskName,
+    pub start: i32,
+    /// Second the task is done, it is worked on until the second before
+    pub end: i32,
+}
+
+/// Who works on what and when, as simulated by `schedule`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub workers: usize,
+    /// Tasks in the order they were started
+    pub tasks: Vec<Scheduled>,
+    /// Time until every task is done
+    pub total: i32,
+}
+
+impl Schedule {
+    fn working_on(&self, worker: usize, second: i32) -> Option<&Scheduled> {
+        self.tasks
+            .iter()
+            .find(|s| s.worker == worker && s.start <= second && second < s.end)
+    }
+
+    // Tasks done by `second` in the order they were finished
+    fn done_by(&self, second: i32) -> Vec<&str> {
+        let mut done: Vec<&Scheduled> = self.tasks.iter().filter(|s| s.end <= second).collect();
+        done.sort_by(|a, b| (a.end, &a.task).cmp(&(b.end, &b.task)));
+
+        done.iter().map(|s| s.task.as_str()).collect()
+    }
+
+    /// Second by second table of what each worker is doing, `.` when idle,
+    /// and the tasks done so far, like the one in the puzzle.
+    pub fn render_gantt(&self) -> String {
+        let mut header = vec!["Second".to_string()];
+        header.extend((1..=self.workers).map(|w| format!("Worker {}", w)));
+        header.push("Done".to_string());
+        let header: Vec<&str> = header.iter().map(|h| h.as_str()).collect();
+
+        // Single letter tasks read best run together as in the puzzle
+        let separator = if self.tasks.iter().all(|s| s.task.chars().count() == 1) {
+            ""
+        } else {
+            ","
+        };
 
-        let task = ts.pop().unwrap();
+        let cells: Vec<Vec<String>> = (0..=self.total)
+            .map(|second| {
+                let mut row = vec![second.to_string()];
+                for w in 1..=self.workers {
+                    row.push(
+                        self.working_on(w, second)
+                            .map_or(".".to_string(), |s| s.task.clone()),
+                    );
+                }
+                row.push(self.done_by(second).join(separator));
+                row
+            })
+            .collect();
 
-        if tasks.complete_task(task) {
-            completion_list.push(task);
+        table(&header, &cells)
+    }
+
+    /// The scheduled tasks as CSV with a `worker,task,start,end` header.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("worker,task,start,end\n");
+
+        for s in &self.tasks {
+            let task = if s.task.contains([',', '"']) {
+                format!("\"{}\"", s.task.replace('"', "\"\""))
+            } else {
+                s.task.clone()
+            };
+            out.push_str(&format!("{},{},{},{}\n", s.worker, task, s.start, s.end));
         }
+
+        out
     }
+}
 
-    completion_list
+/// Time for `workers` to complete all tasks when each task takes
+/// `work_time` plus its `task_time` (nothing extra outside `A`-`Z`).
+pub fn process_tasks_parrallel(tasks: TaskMap, workers: usize, work_time: i32) -> CycleResult<i32> {
+    process_tasks_parrallel_with(tasks, workers, puzzle_duration(work_time))
 }
 
-fn start_work(
-    queue: &Vec<TaskName>,
-    events: &mut Vec<(i32, TaskName)>,
+/// Like `process_tasks_parrallel`, with `duration` giving the seconds each
+/// task takes.
+pub fn process_tasks_parrallel_with<F: Fn(&str) -> i32>(
+    tasks: TaskMap,
     workers: usize,
-    work_time: i32,
-    time: i32,
-) -> Vec<TaskName> {
-    let mut queue = queue.clone();
+    duration: F,
+) -> CycleResult<i32> {
+    schedule_with(tasks, workers, duration).map(|s| s.total)
+}
+
+/// The schedule behind `process_tasks_parrallel`.
+pub fn schedule(tasks: TaskMap, workers: usize, work_time: i32) -> CycleResult<Schedule> {
+    schedule_with(tasks, workers, puzzle_duration(work_time))
+}
+
+/// Simulates `workers` (at least one) working through the tasks, each
+/// taking the alphabetically first available task whenever it is idle.
+pub fn schedule_with<F: Fn(&str) -> i32>(
+    tasks: TaskMap,
+    workers: usize,
+    duration: F,
+) -> CycleResult<Schedule> {
+    schedule_by(tasks, workers, duration, &Alphabetical)
+}
+
+/// Like `schedule_with`, idle workers taking the available task `policy`
+/// picks first.
+pub fn schedule_by<F: Fn(&str) -> i32, P: Policy + ?Sized>(
+    mut tasks: TaskMap,
+    workers: usize,
+    duration: F,
+    policy: &P,
+) -> CycleResult<Schedule> {
+    tasks.validate()?;
+
+    let workers = workers.max(1);
+    let mut schedule = Schedule {
+        workers,
+        tasks: vec![],
+        total: 0,
+    };
+    let mut events = Events::new();
+    let mut queue = Queue::new();
+    queue_tasks(&mut queue, tasks.completable_tasks(), policy);
+    let mut idle: BTreeSet<usize> = (1..=workers).collect();
+    let mut time = 0;
 
-    loop {
-        if events.len() >= workers || queue.is_empty() {
-            break;
+    start_work(
+        &mut queue,
+        &mut events,
+        &mut idle,
+        &mut schedule,
+        &duration,
+        time,
+    );
+
+    while let Some(Reverse((finish, task, worker))) = events.pop() {
+        time = finish;
+        queue_tasks(&mut queue, tasks.release(&task), policy);
+        idle.insert(worker);
+
+        // Tasks finishing at the same moment free up their dependents together
+        while let Some(&Reverse((next, _, _))) = events.peek() {
+            if next != time {
+                break;
+            }
+            if let Some(Reverse((_, task, worker))) = events.pop() {
+                queue_tasks(&mut queue, tasks.release(&task), policy);
+                idle.insert(worker);
+            }
         }
 
-        let current = events.clone();
-        let m = current.iter().min();
+        start_work(
+            &mut queue,
+            &mut events,
+            &mut idle,
+            &mut schedule,
+            &duration,
+            time,
+        );
+    }
+
+    schedule.total = time;
+    Ok(schedule)
+}
+
+/// When a task can run within the shortest possible schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskTiming {
+    pub task: TaskName,
+    pub duration: i32,
+    /// Earliest start once all its dependencies are finished
+    pub earliest_start: i32,
+    /// Latest start that doesn't delay the end of the schedule
+    pub latest_start: i32,
+}
 
-        if let Some((t, x)) = m {
-            let task = *x;
-            queue = queue.into_iter().filter(|&t| t != task).collect();
-            println!("Starting {} at {}", task, time);
-            events.push((time + work_time + task_time(task).unwrap(), task));
+impl TaskTiming {
+    /// How long the task can be delayed without delaying the end.
+    pub fn slack(&self) -> i32 {
+        self.latest_start - self.earliest_start
+    }
+}
+
+/// Critical path analysis of a task graph with unlimited workers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// Timings in the order `process_tasks` completes the tasks
+    pub timings: Vec<TaskTiming>,
+    /// Longest chain of dependent tasks, first task first
+    pub critical_path: Vec<TaskName>,
+    /// Length of the critical path, the shortest possible schedule
+    pub length: i32,
+    /// Sum of all task durations
+    pub total_work: i32,
+}
+
+impl Analysis {
+    /// No schedule with `workers` workers can finish sooner than this.
+    pub fn lower_bound(&self, workers: usize) -> i32 {
+        let workers = workers.max(1) as i32;
+        let shared = (self.total_work + workers - 1) / workers;
+
+        self.length.max(shared)
+    }
+
+    /// Table of the timings followed by the critical path and lower bounds
+    /// for 1 to `max_workers` workers.
+    pub fn render(&self, max_workers: usize) -> String {
+        let header = ["Task", "Duration", "Earliest", "Latest", "Slack"];
+        let cells: Vec<Vec<String>> = self
+            .timings
+            .iter()
+            .map(|t| {
+                vec![
+                    t.task.to_string(),
+                    t.duration.to_string(),
+                    t.earliest_start.to_string(),
+                    t.latest_start.to_string(),
+                    t.slack().to_string(),
+                ]
+            })
+            .collect();
+
+        let mut out = table(&header, &cells);
+        out.push_str(&format!(
+            "\nCritical path: {} ({}s)\n",
+            self.critical_path.join(" -> "),
+            self.length
+        ));
+        for workers in 1..=max_workers {
+            out.push_str(&format!(
+                "Lower bound with {} worker(s): {}s\n",
+                workers,
+                self.lower_bound(workers)
+            ));
         }
+
+        out
     }
+}
 
-    queue
+/// Earliest and latest start of every task when each one takes `work_time`
+/// plus its `task_time`, and the longest chain through them.
+pub fn analyse(tasks: &TaskMap, work_time: i32) -> CycleResult<Analysis> {
+    analyse_with(tasks, puzzle_duration(work_time))
 }
 
-fn process_tasks_parrallel(mut tasks: TaskMap, workers: usize, work_time: i32) -> i32 {
-    // let mut events: Vec<(i32, TaskName)> = vec![];
-    // let mut queue: Vec<TaskName> = vec![];
-    // let mut time = 0;
+/// Like `analyse`, with `duration` giving the seconds each task takes.
+pub fn analyse_with<F: Fn(&str) -> i32>(tasks: &TaskMap, duration: F) -> CycleResult<Analysis> {
+    let order = process_tasks(tasks.clone())?;
 
-    // let mut ts = tasks.completable_tasks();
+    let mut earliest: HashMap<&str, i32> = HashMap::new();
+    for t in &order {
+        let start = tasks.tmap[t]
+            .iter()
+            .map(|d| earliest[d.as_str()] + duration(d))
+            .max()
+            .unwrap_or(0);
+        earliest.insert(t, start);
+    }
 
-    // for &t in &ts {
-    //     queue.push(t);
-    // }
+    let length = order
+        .iter()
+        .map(|t| earliest[t.as_str()] + duration(t))
+        .max()
+        .unwrap_or(0);
 
-    // queue = start_work(&queue, &mut events, workers, work_time, time);
+    let mut latest: HashMap<&str, i32> = HashMap::new();
+    for t in order.iter().rev() {
+        let finish = tasks
+            .tmap
+            .iter()
+            .filter(|&(_, ds)| ds.contains(t))
+            .map(|(w, _)| latest[w.as_str()])
+            .min()
+            .unwrap_or(length);
+        latest.insert(t, finish - duration(t));
+    }
 
-    // while !events.is_empty() || !queue.is_empty() {
-    // }
+    let timings: Vec<TaskTiming> = order
+        .iter()
+        .map(|t| TaskTiming {
+            task: t.clone(),
+            duration: duration(t),
+            earliest_start: earliest[t.as_str()],
+            latest_start: latest[t.as_str()],
+        })
+        .collect();
 
-    // time
+    // Follow tasks without slack, each waiting on the previous one and
+    // starting as soon as it ends
+    let mut critical_path: Vec<TaskName> = vec![];
+    let mut time = 0;
+    while let Some(t) = timings.iter().find(|t| {
+        t.slack() == 0
+            && t.earliest_start == time
+            && critical_path
+                .last()
+                .is_none_or(|p| tasks.tmap[&t.task].contains(p))
+    }) {
+        critical_path.push(t.task.clone());
+        time += t.duration;
+    }
 
-    unimplemented!();
+    Ok(Analysis {
+        total_work: timings.iter().map(|t| t.duration).sum(),
+        timings,
+        critical_path,
+        length,
+    })
 }
 
-fn task_time(t: TaskName) -> Option<i32> {
-    if 'A' <= t && t <= 'Z' {
-        let time = 1 + t as i32 - 'A' as i32;
-        Some(time)
-    } else {
-        None
+/// Extra seconds for a task: 1 for `A` through 26 for `Z`, `None` for
+/// anything but a single capital letter.
+pub fn task_time(t: &str) -> Option<i32> {
+    let mut chars = t.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_uppercase() => Some(1 + c as i32 - 'A' as i32),
+        _ => None,
     }
 }
 
-pub fn day7(input: &str) {
-    let f = File::open(input).expect("Failed to open input file");
-    let reader = BufReader::new(f);
+/// The puzzle's durations: `work_time` plus the `task_time`, which is
+/// nothing for labels other than `A`-`Z`.
+pub fn puzzle_duration(work_time: i32) -> impl Fn(&str) -> i32 {
+    move |t| work_time + task_time(t).unwrap_or(0)
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The Sum of Its Parts.
+pub struct Day7;
 
-    let mut tasks = TaskMap::empty();
+impl Solution for Day7 {
+    type Input = TaskMap;
+    type Part1 = String;
+    type Part2 = i32;
 
-    for line in reader.lines() {
-        let trecord = line.expect("failed to read input line");
+    fn parse(&self, input: &str) -> Result<TaskMap> {
+        let mut tasks = TaskMap::empty();
 
-        if let Ok(dep) = Dependency::parse(&trecord) {
+        for dep in error::parse_lines(input, Dependency::parse)? {
             tasks.add(dep);
-        } else {
-            println!("Failed to parse line: {}", trecord);
         }
+
+        Ok(tasks)
+    }
+
+    fn part1(&self, tasks: &TaskMap) -> Result<String> {
+        Ok(process_tasks(tasks.clone())?.concat())
+    }
+
+    fn part2(&self, tasks: &TaskMap) -> Result<i32> {
+        Ok(process_tasks_parrallel(tasks.clone(), WORKERS, WORK_TIME)?)
     }
+}
+
+/// `--dot`: the task graph as Graphviz DOT, coloured by completion step
+/// unless `style` is `plain` or the graph has a cycle.
+pub fn dot_view(input: &str, style: Option<&str>) -> Result<String> {
+    let tasks = Day7.parse(input)?;
+
+    let order = if style != Some("plain") {
+        process_tasks(tasks.clone()).ok()
+    } else {
+        None
+    };
+
+    Ok(tasks.to_dot(order.as_deref()))
+}
+
+/// `--critical-path`: the critical path, slack and lower bounds of the
+/// puzzle's graph.
+pub fn critical_path_view(input: &str, _: Option<&str>) -> Result<String> {
+    Ok(analyse(&Day7.parse(input)?, WORK_TIME)?.render(WORKERS))
+}
 
-    let cstring: String = process_tasks(tasks.clone()).iter().collect();
-    println!("Completion order: {:?}", cstring);
+/// `--gantt`: the part 2 worker schedule, second by second, or as CSV when
+/// `format` is `csv`.
+pub fn gantt_view(input: &str, format: Option<&str>) -> Result<String> {
+    let schedule = schedule(Day7.parse(input)?, WORKERS, WORK_TIME)?;
 
-    let time_took = process_tasks_parrallel(tasks, 5, 60);
-    println!("Parallel completion time: {:?}", time_took);
+    if format == Some("csv") {
+        Ok(schedule.to_csv())
+    } else {
+        Ok(schedule.render_gantt())
+    }
 }
 
 #[test]
@@ -226,8 +851,8 @@ fn test_dependency_parse() {
         assert_eq!(
             dep,
             Dependency {
-                task: 'A',
-                depends_on: 'C'
+                task: "A".to_string(),
+                depends_on: "C".to_string()
             }
         );
     } else {
@@ -238,8 +863,8 @@ fn test_dependency_parse() {
         assert_eq!(
             dep,
             Dependency {
-                task: 'F',
-                depends_on: 'C'
+                task: "F".to_string(),
+                depends_on: "C".to_string()
             }
         );
     } else {
@@ -247,8 +872,9 @@ fn test_dependency_parse() {
     }
 }
 
-#[test]
-fn test_process_tasks() {
+// The example graph from the puzzle description
+#[cfg(test)]
+fn sample_tasks() -> TaskMap {
     let mut tasks = TaskMap::empty();
     tasks.add(Dependency::new('A', 'C'));
     tasks.add(Dependency::new('F', 'C'));
@@ -257,32 +883,215 @@ fn test_process_tasks() {
     tasks.add(Dependency::new('E', 'B'));
     tasks.add(Dependency::new('E', 'D'));
     tasks.add(Dependency::new('E', 'F'));
+    tasks
+}
+
+#[test]
+fn test_process_tasks() {
+    let tasks = sample_tasks();
 
     println!("Task map: {:?}", tasks);
 
-    let clist = process_tasks(tasks);
+    let clist = process_tasks(tasks).unwrap();
 
-    assert_eq!(clist.iter().collect::<String>(), "CABDFE");
+    assert_eq!(clist.concat(), "CABDFE");
 }
 
-// #[test]
-// fn test_process_tasks_parrallel() {
-//     let mut tasks = TaskMap::empty();
-//     tasks.add(Dependency::new('A', 'C'));
-//     tasks.add(Dependency::new('F', 'C'));
-//     tasks.add(Dependency::new('B', 'A'));
-//     tasks.add(Dependency::new('D', 'A'));
-//     tasks.add(Dependency::new('E', 'B'));
-//     tasks.add(Dependency::new('E', 'D'));
-//     tasks.add(Dependency::new('E', 'F'));
+#[test]
+fn test_process_tasks_parrallel() {
+    let tasks = sample_tasks();
 
-//     let took_time = process_tasks_parrallel(tasks, 5, 60);
-//     assert_eq!(time_took, 15);
-// }
+    let time_took = process_tasks_parrallel(tasks.clone(), 2, 0).unwrap();
+    assert_eq!(time_took, 15);
+
+    // A single worker has to do everything one after the other
+    let time_took = process_tasks_parrallel(tasks, 1, 0).unwrap();
+    assert_eq!(time_took, 21);
+}
 
 #[test]
 fn test_task_time() {
     ('A' as u8..'Z' as u8)
         .enumerate()
-        .for_each(|(i, c)| assert_eq!(task_time(c as TaskName), Some(i as i32 + 1)));
+        .for_each(|(i, c)| assert_eq!(task_time(&(c as char).to_string()), Some(i as i32 + 1)));
+    assert_eq!(task_time("a"), None);
+    assert_eq!(task_time("AB"), None);
+}
+
+#[test]
+fn test_validate() {
+    let mut tasks = TaskMap::empty();
+    tasks.add(Dependency::new('B', 'A'));
+    tasks.add(Dependency::new('C', 'B'));
+    assert_eq!(tasks.validate(), Ok(()));
+
+    // A before B before C before B, and D waits on C
+    tasks.add(Dependency::new('B', 'C'));
+    tasks.add(Dependency::new('D', 'C'));
+
+    let err = tasks.validate().unwrap_err();
+    assert_eq!(err.cycle, vec!["B", "C", "B"]);
+    assert_eq!(err.blocked, vec!["D"]);
+    assert_eq!(
+        err.to_string(),
+        "Dependency cycle: B -> C -> B (also blocks D)"
+    );
+    assert_eq!(process_tasks_parrallel(tasks, 2, 0), Err(err));
+}
+
+#[test]
+fn test_to_dot() {
+    let mut tasks = TaskMap::empty();
+    tasks.add(Dependency::new('B', 'A'));
+    tasks.add(Dependency::new('C', 'A'));
+
+    assert_eq!(
+        tasks.to_dot(None),
+        "digraph day7 {\n    rankdir=LR;\n    node [shape=box];\n    \
+         \"A\" [label=\"A (1s)\"];\n    \
+         \"B\" [label=\"B (2s)\"];\n    \
+         \"C\" [label=\"C (3s)\"];\n    \
+         \"A\" -> \"B\";\n    \
+         \"A\" -> \"C\";\n}\n"
+    );
+
+    let order = process_tasks(tasks.clone()).unwrap();
+    let dot = tasks.to_dot(Some(&order));
+    assert!(dot.contains(
+        "\"A\" [label=\"A (1s)\\nstep 1\", style=filled, fillcolor=\"0.000 0.400 1.000\"];"
+    ));
+    assert!(dot.contains(
+        "\"C\" [label=\"C (3s)\\nstep 3\", style=filled, fillcolor=\"0.667 0.400 1.000\"];"
+    ));
+}
+
+#[test]
+fn test_analyse() {
+    let tasks = sample_tasks();
+
+    let analysis = analyse(&tasks, 0).unwrap();
+
+    assert_eq!(analysis.critical_path, vec!["C", "F", "E"]);
+    assert_eq!(analysis.length, 14);
+    assert_eq!(analysis.total_work, 21);
+
+    let slack: Vec<(&str, i32, i32)> = analysis
+        .timings
+        .iter()
+        .map(|t| (t.task.as_str(), t.earliest_start, t.slack()))
+        .collect();
+    assert_eq!(
+        slack,
+        vec![
+            ("C", 0, 0),
+            ("A", 3, 1),
+            ("B", 4, 3),
+            ("D", 4, 1),
+            ("F", 3, 0),
+            ("E", 9, 0)
+        ]
+    );
+
+    assert_eq!(analysis.lower_bound(1), 21);
+    assert_eq!(analysis.lower_bound(2), 14);
+    assert!(analysis.lower_bound(2) <= process_tasks_parrallel(tasks, 2, 0).unwrap());
+}
+
+#[test]
+fn test_named_steps() {
+    let dep =
+        Dependency::parse("Step fetch deps must be finished before step build-core can begin.")
+            .unwrap();
+    assert_eq!(dep, Dependency::new("build-core", "fetch deps"));
+    assert!(Dependency::parse("Step must be finished before step A can begin.").is_err());
+
+    let tasks = Day7
+        .parse(
+            "Step fetch must be finished before step build can begin.\n\
+             Step build must be finished before step test can begin.\n\
+             Step fetch must be finished before step docs can begin.",
+        )
+        .unwrap();
+
+    assert_eq!(
+        process_tasks(tasks.clone()).unwrap(),
+        vec!["fetch", "build", "docs", "test"]
+    );
+
+    let duration = |t: &str| match t {
+        "build" => 30,
+        "test" => 20,
+        _ => 5,
+    };
+    assert_eq!(
+        process_tasks_parrallel_with(tasks.clone(), 2, duration),
+        Ok(55)
+    );
+    assert_eq!(
+        analyse_with(&tasks, duration).unwrap().critical_path,
+        vec!["fetch", "build", "test"]
+    );
+}
+
+#[test]
+fn test_schedule() {
+    let tasks = sample_tasks();
+
+    let schedule = schedule(tasks, 2, 0).unwrap();
+    let gantt = schedule.render_gantt();
+    let lines: Vec<&str> = gantt.lines().collect();
+
+    assert_eq!(lines[0], "Second | Worker 1 | Worker 2 | Done");
+    assert_eq!(lines[2], "0      | C        | .        |");
+    assert_eq!(lines[5], "3      | A        | F        | C");
+    assert_eq!(lines[11], "9      | D        | .        | CABF");
+    assert_eq!(lines[17], "15     | .        | .        | CABFDE");
+    assert_eq!(lines.len(), 18);
+
+    let csv = schedule.to_csv();
+    assert!(csv.starts_with("worker,task,start,end\n1,C,0,3\n1,A,3,4\n2,F,3,9\n"));
+    assert!(csv.ends_with("1,E,10,15\n"));
+}
+
+#[test]
+fn test_policies() {
+    let tasks = sample_tasks();
+
+    let order = |policy: &dyn Policy| process_tasks_by(tasks.clone(), policy).unwrap().concat();
+
+    assert_eq!(order(&Alphabetical), "CABDFE");
+    assert_eq!(
+        order(&LongestFirst {
+            duration: puzzle_duration(0)
+        }),
+        "CFADBE"
+    );
+    assert_eq!(order(&MostDependentsFirst::new(&tasks)), "CABDFE");
+    assert_eq!(order(&|a: &str, b: &str| b.cmp(a)), "CFADBE");
+
+    // A and B are both available but B unblocks more, so goes first
+    let mut fanout = TaskMap::empty();
+    fanout.add(Dependency::new('C', 'B'));
+    fanout.add(Dependency::new('E', 'B'));
+    fanout.add(Dependency::new('D', 'A'));
+    let by_dependents = MostDependentsFirst::new(&fanout);
+    assert_eq!(
+        process_tasks_by(fanout.clone(), &Alphabetical)
+            .unwrap()
+            .concat(),
+        "ABCDE"
+    );
+    assert_eq!(
+        process_tasks_by(fanout, &by_dependents).unwrap().concat(),
+        "BACDE"
+    );
+
+    // F and D, the longer of the tasks available together, start first
+    let longest = LongestFirst {
+        duration: puzzle_duration(0),
+    };
+    let by_length = schedule_by(tasks.clone(), 2, puzzle_duration(0), &longest).unwrap();
+    let started: Vec<&str> = by_length.tasks.iter().map(|s| s.task.as_str()).collect();
+    assert_eq!(started, vec!["C", "F", "A", "D", "B", "E"]);
+    assert_eq!(by_length.total, schedule(tasks, 2, 0).unwrap().total);
 }
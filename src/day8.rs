@@ -1,86 +1,762 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::iter::Iterator;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
 
-#[derive(Debug)]
-struct Node {
-    children: Vec<Box<Node>>,
+use error::{Error, Result};
+use solution::Solution;
+
+/// Position of a node in its tree, numbered in pre-order from the root at 0.
+pub type NodeId = usize;
+
+// A node's slices of the tree's flat storage. As nodes are numbered in
+// pre-order, the subtree of a node is the range `id..end`.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    children: Range<usize>,
+    metadata: Range<usize>,
+    end: NodeId,
+}
+
+/// A license tree, kept as a flat arena of nodes so that building, walking
+/// and dropping even very deep trees needs no recursion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    entries: Vec<Entry>,
+    child_ids: Vec<NodeId>,
     metadata: Vec<i32>,
 }
 
+/// A node somewhere in a `Node` tree.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRef<'t> {
+    tree: &'t Node,
+    id: NodeId,
+}
+
+// A node being built whose children are still to come
+struct Frame {
+    id: NodeId,
+    children_left: usize,
+    metadata: usize,
+    children: Vec<NodeId>,
+}
+
 impl Node {
-    fn new(children: Vec<Box<Node>>, metadata: Vec<i32>) -> Node {
-        Node { children, metadata }
+    /// Tree with `children` under a new root holding `metadata`.
+    pub fn new(children: Vec<Node>, metadata: Vec<i32>) -> Node {
+        let mut tree = Node {
+            entries: vec![Entry {
+                children: 0..0,
+                metadata: 0..0,
+                end: 0,
+            }],
+            child_ids: vec![],
+            metadata: vec![],
+        };
+
+        // Children's storage goes before the root's, as if it was parsed
+        let mut roots = vec![];
+        for child in children {
+            let base = tree.entries.len();
+            let cbase = tree.child_ids.len();
+            let mbase = tree.metadata.len();
+
+            roots.push(base);
+            tree.entries
+                .extend(child.entries.into_iter().map(|e| Entry {
+                    children: e.children.start + cbase..e.children.end + cbase,
+                    metadata: e.metadata.start + mbase..e.metadata.end + mbase,
+                    end: e.end + base,
+                }));
+            tree.child_ids
+                .extend(child.child_ids.into_iter().map(|id| id + base));
+            tree.metadata.extend(child.metadata);
+        }
+
+        tree.entries[0] = Entry {
+            children: tree.child_ids.len()..tree.child_ids.len() + roots.len(),
+            metadata: tree.metadata.len()..tree.metadata.len() + metadata.len(),
+            end: tree.entries.len(),
+        };
+        tree.child_ids.extend(roots);
+        tree.metadata.extend(metadata);
+
+        tree
+    }
+
+    /// The root node, whose id is 0.
+    pub fn root(&self) -> NodeRef<'_> {
+        NodeRef { tree: self, id: 0 }
+    }
+
+    /// Node `id`, if the tree has that many nodes.
+    pub fn get(&self, id: NodeId) -> Option<NodeRef<'_>> {
+        if id < self.entries.len() {
+            Some(NodeRef { tree: self, id })
+        } else {
+            None
+        }
+    }
+
+    /// Number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Children of the root, in order.
+    pub fn children<'t>(&'t self) -> impl ExactSizeIterator<Item = NodeRef<'t>> + 't {
+        self.root().children()
+    }
+
+    /// Metadata entries of the root.
+    pub fn metadata(&self) -> &[i32] {
+        self.root().metadata()
+    }
+
+    /// Builds a tree from the flat `header, children, metadata` stream.
+    ///
+    /// Panics if the stream isn't exactly one tree, see `from_values`.
+    pub fn from(values: &[i32]) -> Self {
+        Node::from_values(values).unwrap_or_else(|e| panic!("Invalid license tree: {}", e))
+    }
+
+    /// Builds a tree from the flat stream, which must hold exactly one tree.
+    pub fn from_values(values: &[i32]) -> ::std::result::Result<Self, TreeError> {
+        let mut pos = 0;
+        let node = Node::build(values, &mut pos)?;
+
+        if pos < values.len() {
+            return Err(TreeError::TrailingValues {
+                token: pos,
+                count: values.len() - pos,
+            });
+        }
+
+        Ok(node)
+    }
+
+    /// Parses numbers separated by any whitespace into a tree.
+    pub fn parse(input: &str) -> ::std::result::Result<Self, TreeError> {
+        let values = tokens(input)
+            .iter()
+            .enumerate()
+            .map(|(n, &(_, text))| {
+                text.parse().map_err(|_| TreeError::InvalidNumber {
+                    token: n,
+                    text: text.to_string(),
+                })
+            })
+            .collect::<::std::result::Result<Vec<i32>, TreeError>>()?;
+
+        Node::from_values(&values)
+    }
+
+    // Reads one tree from `values` at `pos`, keeping the nodes still waiting
+    // on children on an explicit stack.
+    fn build(values: &[i32], pos: &mut usize) -> ::std::result::Result<Self, TreeError> {
+        let mut tree = Node {
+            entries: vec![],
+            child_ids: vec![],
+            metadata: vec![],
+        };
+        let mut stack: Vec<Frame> = vec![];
+
+        loop {
+            let children_left = next_count(values, pos)?;
+            let metadata = next_count(values, pos)?;
+
+            let id = tree.entries.len();
+            tree.entries.push(Entry {
+                children: 0..0,
+                metadata: 0..0,
+                end: 0,
+            });
+            if let Some(parent) = stack.last_mut() {
+                parent.children_left -= 1;
+                parent.children.push(id);
+            }
+            stack.push(Frame {
+                id,
+                children_left,
+                metadata,
+                children: vec![],
+            });
+
+            // Nodes with all their children read are followed by metadata
+            while stack.last().is_some_and(|f| f.children_left == 0) {
+                let frame = stack.pop().unwrap();
+
+                let mstart = tree.metadata.len();
+                for _ in 0..frame.metadata {
+                    tree.metadata.push(next_value(values, pos)?);
+                }
+                let cstart = tree.child_ids.len();
+                tree.child_ids.extend(frame.children);
+
+                tree.entries[frame.id] = Entry {
+                    children: cstart..tree.child_ids.len(),
+                    metadata: mstart..tree.metadata.len(),
+                    end: tree.entries.len(),
+                };
+            }
+
+            if stack.is_empty() {
+                return Ok(tree);
+            }
+        }
+    }
+
+    /// Sum of the metadata of every node.
+    pub fn sum_metadata(&self) -> i32 {
+        self.metadata.iter().sum()
+    }
+
+    /// Part 2 value of the root, see `NodeRef::node_value`.
+    pub fn node_value(&self) -> i32 {
+        self.root().node_value()
+    }
+
+    // Part 2 value of each node of the subtree `ids`
+    fn values(&self, ids: Range<NodeId>) -> Vec<i32> {
+        let start = ids.start;
+
+        // Children come after their parent, so going backwards every child
+        // is valued before it is needed
+        let mut values = vec![0; ids.len()];
+        for id in ids.rev() {
+            let e = &self.entries[id];
+            let metadata = &self.metadata[e.metadata.clone()];
+            let children = &self.child_ids[e.children.clone()];
+
+            values[id - start] = if children.is_empty() {
+                metadata.iter().sum()
+            } else {
+                metadata
+                    .iter()
+                    .filter(|&&m| m >= 1)
+                    .filter_map(|&m| children.get(m as usize - 1))
+                    .map(|&c| values[c - start])
+                    .sum()
+            };
+        }
+
+        values
+    }
+
+    /// The tree as the flat `header, children, metadata` stream, so that
+    /// `Node::from(&node.to_values()) == node`.
+    pub fn to_values(&self) -> Vec<i32> {
+        let mut values = Vec::with_capacity(2 * self.entries.len() + self.metadata.len());
+        let mut open: Vec<NodeId> = vec![];
+
+        for id in 0..=self.entries.len() {
+            // Subtrees ending before this node are closed by their metadata
+            while open.last().is_some_and(|&top| self.entries[top].end <= id) {
+                let top = open.pop().unwrap();
+                values.extend_from_slice(&self.metadata[self.entries[top].metadata.clone()]);
+            }
+
+            if let Some(e) = self.entries.get(id) {
+                values.push(e.children.len() as i32);
+                values.push(e.metadata.len() as i32);
+                open.push(id);
+            }
+        }
+
+        values
+    }
+
+    /// One line per node, indented by depth, with its metadata and both
+    /// part 1 and part 2 totals for its subtree.
+    pub fn render_tree(&self) -> String {
+        let values = self.values(0..self.entries.len());
+
+        let mut sums = vec![0; self.entries.len()];
+        for node in self.post_order() {
+            sums[node.id] = node.metadata().iter().sum::<i32>()
+                + node.children().map(|c| sums[c.id]).sum::<i32>();
+        }
+
+        self.fold(String::new(), |mut out, node, depth| {
+            out.push_str(&format!(
+                "{:indent$}#{} metadata {:?} sum {} value {}\n",
+                "",
+                node.id,
+                node.metadata(),
+                sums[node.id],
+                values[node.id],
+                indent = 2 * depth
+            ));
+            out
+        })
+    }
+
+    /// Every node, parents before their children, see `NodeRef::pre_order`.
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        self.root().pre_order()
+    }
+
+    /// Every node, children before their parents.
+    pub fn post_order(&self) -> PostOrder<'_> {
+        self.root().post_order()
+    }
+
+    /// Folds every node in pre-order along with its depth, the root being 0.
+    pub fn fold<B, F>(&self, init: B, f: F) -> B
+    where
+        F: FnMut(B, NodeRef, usize) -> B,
+    {
+        self.root().fold(init, f)
+    }
+
+    /// The nodes from the root down to node `id`, or `None` if there is no
+    /// such node.
+    pub fn path_to(&self, id: NodeId) -> Option<Vec<NodeRef<'_>>> {
+        self.root().path_to(id)
+    }
+}
+
+impl<'t> NodeRef<'t> {
+    /// Position of this node in its tree.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn entry(&self) -> &'t Entry {
+        &self.tree.entries[self.id]
+    }
+
+    /// Direct children of this node, in order.
+    pub fn children(&self) -> impl ExactSizeIterator<Item = NodeRef<'t>> + 't {
+        let tree = self.tree;
+
+        tree.child_ids[self.entry().children.clone()]
+            .iter()
+            .map(move |&id| NodeRef { tree, id })
+    }
+
+    /// Metadata entries of this node alone.
+    pub fn metadata(&self) -> &'t [i32] {
+        &self.tree.metadata[self.entry().metadata.clone()]
+    }
+
+    /// Whether this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.entry().children.is_empty()
+    }
+
+    /// Sum of the metadata of this node and all its descendants.
+    pub fn sum_metadata(&self) -> i32 {
+        self.pre_order()
+            .map(|node| node.metadata().iter().sum::<i32>())
+            .sum()
+    }
+
+    /// Part 2: a leaf is worth its metadata sum, otherwise metadata entries
+    /// are 1-based indexes of the children to add up.
+    pub fn node_value(&self) -> i32 {
+        self.tree.values(self.id..self.entry().end)[0]
+    }
+
+    /// This node and its descendants, parents before their children and
+    /// siblings in order.
+    pub fn pre_order(&self) -> PreOrder<'t> {
+        PreOrder {
+            tree: self.tree,
+            next: self.id,
+            end: self.entry().end,
+            ends: vec![],
+        }
+    }
+
+    /// This node and its descendants, children before their parents.
+    pub fn post_order(&self) -> PostOrder<'t> {
+        PostOrder {
+            tree: self.tree,
+            stack: vec![(self.id, 0)],
+        }
+    }
+
+    /// Folds this node and its descendants in pre-order, along with their
+    /// depth below this node.
+    pub fn fold<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, NodeRef<'t>, usize) -> B,
+    {
+        let mut pre_order = self.pre_order();
+        let mut acc = init;
+
+        while let Some(node) = pre_order.next() {
+            acc = f(acc, node, pre_order.depth());
+        }
+
+        acc
+    }
+
+    /// The nodes from this one down to node `id`, or `None` if it isn't in
+    /// this subtree.
+    pub fn path_to(&self, id: NodeId) -> Option<Vec<NodeRef<'t>>> {
+        if id < self.id || id >= self.entry().end {
+            return None;
+        }
+
+        // Subtrees are contiguous, so only one child can hold `id`
+        let mut path = vec![*self];
+        let mut node = *self;
+        while node.id != id {
+            node = node.children().find(|c| id < c.entry().end)?;
+            path.push(node);
+        }
+
+        Some(path)
+    }
+}
+
+/// Pre-order walk of a subtree, see `NodeRef::pre_order`.
+pub struct PreOrder<'t> {
+    tree: &'t Node,
+    next: NodeId,
+    end: NodeId,
+    // Ends of the subtrees holding the last node returned
+    ends: Vec<NodeId>,
+}
+
+impl<'t> PreOrder<'t> {
+    /// Depth of the last node returned below the first one.
+    pub fn depth(&self) -> usize {
+        self.ends.len().saturating_sub(1)
+    }
+}
+
+impl<'t> Iterator for PreOrder<'t> {
+    type Item = NodeRef<'t>;
+
+    fn next(&mut self) -> Option<NodeRef<'t>> {
+        if self.next >= self.end {
+            return None;
+        }
+
+        let id = self.next;
+        while self.ends.last().is_some_and(|&end| end <= id) {
+            self.ends.pop();
+        }
+        self.ends.push(self.tree.entries[id].end);
+        self.next += 1;
+
+        Some(NodeRef {
+            tree: self.tree,
+            id,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.end - self.next;
+        (left, Some(left))
+    }
+}
+
+impl<'t> ExactSizeIterator for PreOrder<'t> {}
+
+/// Post-order walk of a subtree, see `NodeRef::post_order`.
+pub struct PostOrder<'t> {
+    tree: &'t Node,
+    // Nodes being walked and how many of their children have been
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'t> Iterator for PostOrder<'t> {
+    type Item = NodeRef<'t>;
+
+    fn next(&mut self) -> Option<NodeRef<'t>> {
+        loop {
+            let (id, walked) = *self.stack.last()?;
+            let children = &self.tree.entries[id].children;
+
+            if walked < children.len() {
+                self.stack.last_mut().unwrap().1 += 1;
+                self.stack
+                    .push((self.tree.child_ids[children.start + walked], 0));
+            } else {
+                self.stack.pop();
+                return Some(NodeRef {
+                    tree: self.tree,
+                    id,
+                });
+            }
+        }
+    }
+}
+
+/// Why a stream of numbers isn't a license tree. Tokens are counted from 0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeError {
+    InvalidNumber {
+        token: usize,
+        text: String,
+    },
+    /// A child or metadata count below zero
+    NegativeCount {
+        token: usize,
+        value: i32,
+    },
+    /// The stream ended while a node still needed values
+    UnexpectedEnd {
+        token: usize,
+    },
+    /// Values left after the root node
+    TrailingValues {
+        token: usize,
+        count: usize,
+    },
+}
+
+impl TreeError {
+    /// Offset of the offending token, the token count for an early end.
+    pub fn token(&self) -> usize {
+        match *self {
+            TreeError::InvalidNumber { token, .. }
+            | TreeError::NegativeCount { token, .. }
+            | TreeError::UnexpectedEnd { token }
+            | TreeError::TrailingValues { token, .. } => token,
+        }
     }
 
-    fn from(values: &[i32]) -> Self {
-        Node::get_node(&mut values.iter())
+    /// Parse error pointing at the line and column of the token in `input`.
+    pub fn locate(&self, input: &str) -> Error {
+        let offset = tokens(input)
+            .get(self.token())
+            .map_or(input.trim_end().len(), |&(offset, _)| offset);
+
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let line = input[..offset].matches('\n').count() + 1;
+        let column = input[line_start..offset].chars().count() + 1;
+
+        Error::parse(line, column, &input[line_start..line_end], self.to_string())
     }
+}
+
+/// The flat stream, space separated, as read by `Node::parse`.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values = self.to_values();
 
-    fn get_node(values: &mut Iterator<Item = &i32>) -> Self {
-        let nc = *values.next().unwrap();
-        let nm = *values.next().unwrap();
+        write!(f, "{}", values[0])?;
+        for value in &values[1..] {
+            write!(f, " {}", value)?;
+        }
+
+        Ok(())
+    }
+}
 
-        let mut children = vec![];
-        for _ in 0..nc {
-            children.push(Box::new(Node::get_node(values)));
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TreeError::InvalidNumber { token, ref text } => {
+                write!(f, "token {}: invalid number {:?}", token, text)
+            }
+            TreeError::NegativeCount { token, value } => {
+                write!(f, "token {}: negative count {}", token, value)
+            }
+            TreeError::UnexpectedEnd { token } => {
+                write!(f, "token {}: input ended in the middle of a node", token)
+            }
+            TreeError::TrailingValues { token, count } => {
+                write!(f, "token {}: {} value(s) after the root node", token, count)
+            }
         }
+    }
+}
 
-        let mut metadata = vec![];
-        for _ in 0..nm {
-            metadata.push(*values.next().unwrap());
+// Whitespace separated tokens with their byte offsets
+fn tokens(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = None;
+
+    for (i, c) in input.char_indices() {
+        if c.is_whitespace() {
+            if let Some(s) = start.take() {
+                tokens.push((s, &input[s..i]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
         }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &input[s..]));
+    }
+
+    tokens
+}
+
+fn next_value(values: &[i32], pos: &mut usize) -> ::std::result::Result<i32, TreeError> {
+    let value = *values
+        .get(*pos)
+        .ok_or(TreeError::UnexpectedEnd { token: *pos })?;
+    *pos += 1;
+
+    Ok(value)
+}
+
+fn next_count(values: &[i32], pos: &mut usize) -> ::std::result::Result<usize, TreeError> {
+    let token = *pos;
+    let value = next_value(values, pos)?;
 
-        Node::new(children, metadata)
+    if value < 0 {
+        return Err(TreeError::NegativeCount { token, value });
     }
 
-    fn sum_metadata(&self) -> i32 {
-        // node metadata sum
-        let msum: i32 = self.metadata.iter().sum();
+    Ok(value as usize)
+}
 
-        // metadata sum of children
-        let csum: i32 = self.children.iter().map(|c| c.sum_metadata()).sum();
+/// How `random_tree` shapes its trees.
+#[derive(Debug, Clone)]
+pub struct Shape {
+    /// Deepest level a node can be at, the root being 0
+    pub depth: usize,
+    /// Number of children of a node above `depth`
+    pub children: RangeInclusive<usize>,
+    /// Number of metadata entries of every node
+    pub metadata: RangeInclusive<usize>,
+    /// Values of the metadata entries
+    pub values: RangeInclusive<i32>,
+}
 
-        // total
-        msum + csum
+impl Default for Shape {
+    fn default() -> Self {
+        Shape {
+            depth: 4,
+            children: 0..=4,
+            metadata: 1..=3,
+            values: 1..=6,
+        }
     }
+}
 
-    // Part 2
-    fn node_value(&self) -> i32 {
-        if self.children.is_empty() {
-            self.metadata.iter().sum()
+/// A generated tree as the flat stream, along with all of its metadata
+/// entries in the order they appear in the stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomTree {
+    pub values: Vec<i32>,
+    pub metadata: Vec<i32>,
+}
+
+/// Generates a valid tree of the given `shape`, always the same one for the
+/// same `seed`.
+pub fn random_tree(seed: u64, shape: &Shape) -> RandomTree {
+    let mut rng = Rng(seed);
+    let mut tree = RandomTree {
+        values: vec![],
+        metadata: vec![],
+    };
+    // Children still to generate and metadata count of the open nodes
+    let mut stack: Vec<(usize, usize)> = vec![];
+
+    loop {
+        let children = if stack.len() < shape.depth {
+            rng.pick(&shape.children)
         } else {
-            let mut total = 0;
-            for &m in &self.metadata {
-                if let Some(child) = self.children.get((m - 1) as usize) {
-                    total += child.node_value();
-                }
+            0
+        };
+        let metadata = rng.pick(&shape.metadata);
+
+        tree.values.push(children as i32);
+        tree.values.push(metadata as i32);
+        stack.push((children, metadata));
+
+        while stack.last().is_some_and(|&(left, _)| left == 0) {
+            let (_, metadata) = stack.pop().unwrap();
+
+            for _ in 0..metadata {
+                let low = *shape.values.start() as i64;
+                let high = *shape.values.end() as i64;
+                let value = (low + rng.below((high - low + 1).max(0) as u64) as i64) as i32;
+
+                tree.values.push(value);
+                tree.metadata.push(value);
             }
 
-            total
+            if let Some(parent) = stack.last_mut() {
+                parent.0 -= 1;
+            }
+        }
+
+        if stack.is_empty() {
+            return tree;
         }
     }
 }
 
-pub fn day8(input: &str) {
-    let mut file = File::open(input).expect("Failed to open input file");
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Failed to read file");
+// SplitMix64, plenty for shaping test trees
+struct Rng(u64);
 
-    let values = contents
-        .trim()
-        .split(" ")
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
 
-    let node = Node::from(&values);
+    // Uniform in `0..n`, or 0 if `n` is 0
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            0
+        } else {
+            self.next() % n
+        }
+    }
 
-    println!("Sum metadata: {}", node.sum_metadata());
+    fn pick(&mut self, range: &RangeInclusive<usize>) -> usize {
+        let (low, high) = (*range.start(), *range.end());
 
-    println!("Part 2 sum: {}", node.node_value());
+        if high < low {
+            low
+        } else {
+            low + self.below((high - low) as u64 + 1) as usize
+        }
+    }
 }
 
+/// Memory Maneuver.
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Node;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, input: &str) -> Result<Node> {
+        Node::parse(input).map_err(|e| e.locate(input))
+    }
+
+    fn part1(&self, node: &Node) -> Result<i32> {
+        Ok(node.sum_metadata())
+    }
+
+    fn part2(&self, node: &Node) -> Result<i32> {
+        Ok(node.node_value())
+    }
+}
+
+/// `--tree`: the tree indented with each node's totals, or as the flat
+/// stream when `format` is `flat`.
+pub fn tree_view(input: &str, format: Option<&str>) -> Result<String> {
+    let node = Day8.parse(input)?;
+
+    if format == Some("flat") {
+        Ok(format!("{}\n", node))
+    } else {
+        Ok(node.render_tree())
+    }
+}
+
+#[cfg(test)]
+use std::collections::BTreeMap;
+
 #[test]
 fn test_sum() {
     let node = Node::from(&vec![2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]);
@@ -92,3 +768,177 @@ fn test_node_value() {
     let node = Node::from(&vec![2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]);
     assert_eq!(node.node_value(), 66);
 }
+
+#[test]
+fn test_parse_errors() {
+    let node = Node::parse("2 3\t0 3 10 11 12\n1 1 0 1 99 2  1 1 2\n").unwrap();
+    assert_eq!(node.sum_metadata(), 138);
+
+    assert_eq!(
+        Node::parse("0 1 9 2 3"),
+        Err(TreeError::TrailingValues { token: 3, count: 2 })
+    );
+    assert_eq!(
+        Node::parse("1 1 0 1"),
+        Err(TreeError::UnexpectedEnd { token: 4 })
+    );
+    assert_eq!(
+        Node::parse("0 -1"),
+        Err(TreeError::NegativeCount {
+            token: 1,
+            value: -1
+        })
+    );
+
+    let err = Day8.parse("0 1\n1 x").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 2, column 3: token 3: invalid number \"x\": \"1 x\""
+    );
+    let err = Day8.parse("1 1\n0 2 5").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 2, column 6: token 5: input ended in the middle of a node: \"0 2 5\""
+    );
+}
+
+#[test]
+fn test_new() {
+    let built = Node::new(
+        vec![
+            Node::new(vec![], vec![10, 11, 12]),
+            Node::new(vec![Node::new(vec![], vec![99])], vec![2]),
+        ],
+        vec![1, 1, 2],
+    );
+
+    assert_eq!(
+        built,
+        Node::from(&[2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2])
+    );
+    assert_eq!(built.node_count(), 4);
+    assert_eq!(built.get(2).unwrap().node_value(), 0);
+    assert_eq!(built.get(2).unwrap().sum_metadata(), 101);
+}
+
+#[test]
+fn test_deep_tree() {
+    // A chain a million nodes deep, each pointing at its only child
+    let depth = 1_000_000;
+    let mut values = vec![];
+    for _ in 0..depth {
+        values.extend_from_slice(&[1, 1]);
+    }
+    values.extend_from_slice(&[0, 1, 7]);
+    values.extend(vec![1; depth]);
+
+    let node = Node::from(&values);
+
+    assert_eq!(node.node_count(), depth + 1);
+    assert_eq!(node.sum_metadata(), depth as i32 + 7);
+    assert_eq!(node.node_value(), 7);
+}
+
+#[test]
+fn test_to_values() {
+    let values = [2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2];
+    let node = Node::from(&values);
+
+    assert_eq!(node.to_values(), values);
+    assert_eq!(node.to_string(), "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+    assert_eq!(Node::parse(&node.to_string()), Ok(node));
+}
+
+#[test]
+fn test_render_tree() {
+    let node = Node::from(&[2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]);
+
+    assert_eq!(
+        node.render_tree(),
+        "#0 metadata [1, 1, 2] sum 138 value 66\n\
+         \x20 #1 metadata [10, 11, 12] sum 33 value 33\n\
+         \x20 #2 metadata [2] sum 101 value 0\n\
+         \x20   #3 metadata [99] sum 99 value 99\n"
+    );
+}
+
+#[test]
+fn test_traversals() {
+    let node = Node::from(&[2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]);
+    let ids = |nodes: Vec<NodeRef>| nodes.iter().map(NodeRef::id).collect::<Vec<_>>();
+
+    assert_eq!(ids(node.pre_order().collect()), [0, 1, 2, 3]);
+    assert_eq!(ids(node.post_order().collect()), [1, 3, 2, 0]);
+    assert_eq!(ids(node.get(2).unwrap().post_order().collect()), [3, 2]);
+
+    assert_eq!(ids(node.path_to(3).unwrap()), [0, 2, 3]);
+    assert_eq!(ids(node.path_to(0).unwrap()), [0]);
+    assert!(node.path_to(4).is_none());
+    assert!(node.get(1).unwrap().path_to(3).is_none());
+
+    let max_depth = node.fold(0, |max, _, depth| max.max(depth));
+    assert_eq!(max_depth, 2);
+
+    let leaves = node.pre_order().filter(NodeRef::is_leaf).count();
+    assert_eq!(leaves, 2);
+
+    let mut histogram = BTreeMap::new();
+    for node in node.pre_order() {
+        for &m in node.metadata() {
+            *histogram.entry(m).or_insert(0) += 1;
+        }
+    }
+    assert_eq!(histogram[&1], 2);
+    assert_eq!(histogram[&2], 2);
+    assert_eq!(histogram.len(), 6);
+
+    let depths = node.fold(vec![], |mut depths, node, depth| {
+        depths.push((node.id(), depth));
+        depths
+    });
+    assert_eq!(depths, [(0, 0), (1, 1), (2, 1), (3, 2)]);
+}
+
+#[test]
+fn test_random_tree() {
+    let shape = Shape::default();
+
+    assert_eq!(random_tree(7, &shape), random_tree(7, &shape));
+    assert_ne!(random_tree(7, &shape), random_tree(8, &shape));
+
+    for seed in 0..500 {
+        let generated = random_tree(seed, &shape);
+        let node = Node::from_values(&generated.values).unwrap();
+
+        assert_eq!(node.to_values(), generated.values, "seed {}", seed);
+        assert_eq!(Node::parse(&node.to_string()).as_ref(), Ok(&node));
+        assert_eq!(node.sum_metadata(), generated.metadata.iter().sum::<i32>());
+        assert!(node.fold(0, |max, _, depth| max.max(depth)) <= shape.depth);
+        assert!(generated.metadata.iter().all(|m| shape.values.contains(m)));
+    }
+}
+
+#[test]
+fn test_random_tree_stress() {
+    // A long chain and a wide, bushy tree
+    let chain = Shape {
+        depth: 200_000,
+        children: 1..=1,
+        metadata: 1..=2,
+        values: 1..=1,
+    };
+    let bushy = Shape {
+        depth: 6,
+        children: 3..=6,
+        metadata: 0..=11,
+        values: -3..=12,
+    };
+
+    for shape in &[chain, bushy] {
+        let generated = random_tree(2018, shape);
+        let node = Node::from_values(&generated.values).unwrap();
+
+        assert_eq!(node.to_values(), generated.values);
+        assert_eq!(node.sum_metadata(), generated.metadata.iter().sum::<i32>());
+    }
+}
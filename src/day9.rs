@@ -1,69 +1,261 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::prelude::*;
-use std::iter::Iterator;
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
 
+use error::{self, Error, Result};
+use solution::Solution;
+use summary::table;
+
+/// Number of players and the value of the last marble.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Game {
+    pub players: u32,
+    pub last_marble: u32,
+}
+
+impl Game {
+    /// Parses `10 players; last marble is worth 1618 points`.
+    pub fn parse(line: &str) -> Result<Game> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                r"^\s*(?P<players>\d+) players; last marble is worth (?P<last>\d+) points\s*$"
+            )
+            .unwrap();
+        }
+
+        match RE.captures(line) {
+            Some(caps) => {
+                let field = |name: &str| -> Result<u32> {
+                    let m = caps.name(name).unwrap();
+                    error::parse_field(line, m.start(), m.as_str())
+                };
+
+                let players = field("players")?;
+                let last_marble = field("last")?;
+
+                if players == 0 {
+                    return Err(Error::parse(1, 1, line, "a game needs at least one player"));
+                }
+
+                Ok(Game {
+                    players,
+                    last_marble,
+                })
+            }
+            None => Err(Error::parse(
+                1,
+                1,
+                line,
+                "expected a game like 10 players; last marble is worth 1618 points",
+            )),
+        }
+    }
+}
+
+// The circle is kept with the current marble at the back, so that clockwise
+// from it is the front and every move is a rotation of a few places.
+//
+// Places `marble` and returns the points scored by it, if any.
 #[inline]
-fn update_row(
-    player: i32,
-    marble: i32,
-    row: &mut Vec<i32>,
-    pos: i32,
-    scores: &mut HashMap<i32, i32>,
-) -> i32 {
-    let n = row.len() as i32;
-    let npos;
-
-    if marble % 23 != 0 {
-        npos = ((pos + 1) % n + 1) % (n + 1);
-        row.insert(npos as usize, marble);
+fn place_marble(circle: &mut VecDeque<u32>, marble: u32) -> Option<u64> {
+    if !marble.is_multiple_of(23) {
+        // Between the marbles 1 and 2 clockwise of the current one
+        circle.rotate_left(1);
+        circle.push_back(marble);
+
+        None
     } else {
-        let to_del = if (pos - 7) > 0 { pos - 7 } else { n + pos - 7 };
-        let del_value = row[to_del as usize];
-        row.remove(to_del as usize);
-        npos = to_del % n;
-        let player_score = scores.entry(player).or_insert(0);
-        *player_score += del_value + marble;
+        // Take the marble 7 counter-clockwise, the one clockwise of it
+        // becomes current
+        circle.rotate_right(7);
+        let removed = circle.pop_back().unwrap_or(0);
+        circle.rotate_left(1);
+
+        Some(u64::from(marble) + u64::from(removed))
     }
+}
 
-    npos
+/// Plays the marble game up to marble `turns`, returning the score of each
+/// player that scored. Player `players` is keyed as 0.
+pub fn play(players: u32, turns: u32) -> HashMap<u32, u64> {
+    play_traced(players, turns, 0).0
 }
 
-fn play(players: i32, turns: i32) -> HashMap<i32, i32> {
-    let mut scores: HashMap<i32, i32> = HashMap::new();
+/// Like `play`, also returning the circle after each of the first
+/// `trace_turns` turns as in the puzzle, e.g. `[3] 0 2 1 (3)` with the
+/// player in brackets and the current marble in parentheses.
+pub fn play_traced(players: u32, turns: u32, trace_turns: u32) -> (HashMap<u32, u64>, Vec<String>) {
+    let mut scores: HashMap<u32, u64> = HashMap::new();
+    let mut trace = vec![];
 
-    let mut current_row: Vec<i32> = vec![0];
-    let mut current_pos = 0;
+    let mut circle = VecDeque::with_capacity(turns as usize + 1);
+    circle.push_back(0);
 
     for marble in 1..=turns {
         let player = marble % players;
-        current_pos = update_row(player, marble, &mut current_row, current_pos, &mut scores);
+        if let Some(points) = place_marble(&mut circle, marble) {
+            *scores.entry(player).or_insert(0) += points;
+        }
+
+        if marble <= trace_turns {
+            let number = if player == 0 { players } else { player };
+            trace.push(format!("[{}] {}", number, render_circle(&circle)));
+        }
     }
 
-    scores
+    (scores, trace)
+}
+
+// Marbles clockwise from 0, the current one in parentheses
+fn render_circle(circle: &VecDeque<u32>) -> String {
+    let start = circle.iter().position(|&m| m == 0).unwrap_or(0);
+    let current = circle.len() - 1;
+
+    (0..circle.len())
+        .map(|i| (start + i) % circle.len())
+        .map(|i| {
+            if i == current {
+                format!("({})", circle[i])
+            } else {
+                circle[i].to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-fn max_score(players: i32, turns: i32) -> Option<i32> {
+/// Table of every player's score from `play`, the winner marked with `*`.
+pub fn render_scores(players: u32, scores: &HashMap<u32, u64>) -> String {
+    let score = |p: u32| scores.get(&(p % players)).cloned().unwrap_or(0);
+    let best = (1..=players).map(score).max().unwrap_or(0);
+
+    let cells: Vec<Vec<String>> = (1..=players)
+        .map(|p| {
+            let mark = if score(p) == best && best > 0 {
+                " *"
+            } else {
+                ""
+            };
+            vec![p.to_string(), format!("{}{}", score(p), mark)]
+        })
+        .collect();
+
+    table(&["Player", "Score"], &cells)
+}
+
+/// Winning score, 0 if no marble scored.
+pub fn max_score(players: u32, turns: u32) -> u64 {
     let scores = play(players, turns);
-    scores.values().max().map(|&n| n)
+    scores.values().max().cloned().unwrap_or(0)
 }
 
-pub fn day9(input: &str) {
-    // let mut file = File::open(input).expect("Failed to open input file");
-    // let mut contents = String::new();
-    // file.read_to_string(&mut contents)
-    //     .expect("Failed to read file");
+/// Marble Mania.
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Game;
+    type Part1 = u64;
+    type Part2 = u64;
 
-    println!("Part 1: {:?}", max_score(470, 72170));
-    println!("Part 2: {:?}", max_score(470, 72170 * 100));
+    fn parse(&self, input: &str) -> Result<Game> {
+        let mut games = error::parse_lines(input, Game::parse)?;
+
+        match games.len() {
+            1 => Ok(games.remove(0)),
+            0 => Err(Error::invalid("No game in input")),
+            n => Err(Error::invalid(format!("Expected one game, found {}", n))),
+        }
+    }
+
+    fn part1(&self, game: &Game) -> Result<u64> {
+        Ok(max_score(game.players, game.last_marble))
+    }
+
+    fn part2(&self, game: &Game) -> Result<u64> {
+        let last = game
+            .last_marble
+            .checked_mul(100)
+            .ok_or_else(|| Error::invalid("Last marble is too large to play 100 times over"))?;
+
+        Ok(max_score(game.players, last))
+    }
+}
+
+/// `--trace`: the first `turns` turns of the part 1 game and every
+/// player's final score.
+pub fn trace_view(input: &str, turns: Option<&str>) -> Result<String> {
+    let game = Day9.parse(input)?;
+    let turns = turns.unwrap_or("0");
+    let turns = turns
+        .parse()
+        .map_err(|_| Error::invalid(format!("Invalid number of turns: {}", turns)))?;
+
+    let (scores, trace) = play_traced(game.players, game.last_marble, turns);
+
+    let mut out = String::new();
+    for line in trace {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push('\n');
+    out.push_str(&render_scores(game.players, &scores));
+
+    Ok(out)
 }
 
 #[test]
 fn test_max_score() {
-    assert_eq!(max_score(9, 25), Some(32));
-    assert_eq!(max_score(10, 1618), Some(8317));
-    assert_eq!(max_score(13, 7999), Some(146373));
-    assert_eq!(max_score(17, 1104), Some(2764));
-    assert_eq!(max_score(21, 6111), Some(54718));
-    assert_eq!(max_score(30, 5807), Some(37305));
+    assert_eq!(max_score(9, 25), 32);
+    assert_eq!(max_score(10, 1618), 8317);
+    assert_eq!(max_score(13, 7999), 146373);
+    assert_eq!(max_score(17, 1104), 2764);
+    assert_eq!(max_score(21, 6111), 54718);
+    assert_eq!(max_score(30, 5807), 37305);
+    assert_eq!(max_score(9, 5), 0);
+    assert_eq!(
+        Day9.solve("9 players; last marble is worth 5 points")
+            .unwrap()
+            .part1,
+        0.into()
+    );
+}
+
+#[test]
+fn test_parse_game() {
+    assert_eq!(
+        Day9.parse("10 players; last marble is worth 1618 points\n")
+            .unwrap(),
+        Game {
+            players: 10,
+            last_marble: 1618
+        }
+    );
+    assert_eq!(
+        Day9
+            .parse("0 players; last marble is worth 5 points")
+            .unwrap_err()
+            .to_string(),
+        "line 1, column 1: a game needs at least one player: \"0 players; last marble is worth 5 points\""
+    );
+    assert!(Day9.parse("").is_err());
+}
+
+#[test]
+fn test_play_traced() {
+    let (scores, trace) = play_traced(9, 25, 25);
+
+    assert_eq!(trace.len(), 25);
+    assert_eq!(trace[0], "[1] 0 (1)");
+    assert_eq!(trace[3], "[4] 0 (4) 2 1 3");
+    assert_eq!(
+        trace[22],
+        "[5] 0 16 8 17 4 18 (19) 2 20 10 21 5 22 11 1 12 6 13 3 14 7 15"
+    );
+    assert_eq!(
+        trace[24],
+        "[7] 0 16 8 17 4 18 19 2 24 20 (25) 10 21 5 22 11 1 12 6 13 3 14 7 15"
+    );
+
+    let table = render_scores(9, &scores);
+    assert!(table.contains("5      | 32 *"));
+    assert!(table.contains("9      | 0"));
 }
@@ -0,0 +1,181 @@
+//! Errors shared by every day and the runner
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading an input or data file failed
+    Io { context: String, source: io::Error },
+    /// Input text that doesn't follow the puzzle's format
+    Parse {
+        file: Option<String>,
+        line: usize,
+        column: usize,
+        text: String,
+        message: String,
+    },
+    /// Well formed input that has no answer, e.g. records out of order
+    Invalid(String),
+}
+
+/// Result of anything that can fail with an `Error`.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl Error {
+    /// Parse error at a 1-based line and column of `text`.
+    pub fn parse<S: Into<String>>(line: usize, column: usize, text: &str, message: S) -> Self {
+        Error::Parse {
+            file: None,
+            line,
+            column,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Input that parses but has no answer.
+    pub fn invalid<S: Into<String>>(message: S) -> Self {
+        Error::Invalid(message.into())
+    }
+
+    /// Moves a parse error of a single line to line `line` of a larger input.
+    pub fn at_line(self, line: usize) -> Self {
+        match self {
+            Error::Parse {
+                file,
+                column,
+                text,
+                message,
+                ..
+            } => Error::Parse {
+                file,
+                line,
+                column,
+                text,
+                message,
+            },
+            e => e,
+        }
+    }
+
+    /// Names the file a parse error was found in.
+    pub fn in_file<S: Into<String>>(self, name: S) -> Self {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                text,
+                message,
+                ..
+            } => Error::Parse {
+                file: Some(name.into()),
+                line,
+                column,
+                text,
+                message,
+            },
+            e => e,
+        }
+    }
+
+    /// Process exit code for the error, following the BSD `sysexits.h` values.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Error::Io { .. } => 74,
+            Error::Parse { .. } => 65,
+            Error::Invalid(_) => 70,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io {
+                ref context,
+                ref source,
+            } => write!(f, "{}: {}", context, source),
+            Error::Parse {
+                ref file,
+                line,
+                column,
+                ref text,
+                ref message,
+            } => {
+                match *file {
+                    Some(ref file) => write!(f, "{}:{}:{}: ", file, line, column)?,
+                    None => write!(f, "line {}, column {}: ", line, column)?,
+                }
+                write!(f, "{}: {:?}", message, text)
+            }
+            Error::Invalid(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io { ref source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Parses `field`, found at byte `offset` of `line`, reporting a failure at
+/// that column.
+pub fn parse_field<T>(line: &str, offset: usize, field: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    field.parse().map_err(|e| {
+        Error::parse(
+            1,
+            offset + 1,
+            line,
+            format!("invalid value {:?}: {}", field, e),
+        )
+    })
+}
+
+/// Parses each non-blank line with `f`, numbering errors by their line.
+pub fn parse_lines<T, F>(input: &str, f: F) -> Result<Vec<T>>
+where
+    F: Fn(&str) -> Result<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .filter(|&(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| f(line).map_err(|e| e.at_line(n + 1)))
+        .collect()
+}
+
+#[test]
+fn test_parse_lines() {
+    let numbers = parse_lines("1\n\n 2\n", |l| parse_field::<i32>(l, 0, l.trim()));
+    assert_eq!(numbers.unwrap(), vec![1, 2]);
+
+    let err = parse_lines("1\n2\nthree", |l| parse_field::<i32>(l, 0, l)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 3, column 1: invalid value \"three\": invalid digit found in string: \"three\""
+    );
+    assert_eq!(err.exit_code(), 65);
+}
+
+#[test]
+fn test_in_file() {
+    let err = Error::parse(1, 7, "#1 @ 1,x: 2x2", "invalid value").at_line(12);
+    assert_eq!(
+        err.in_file("data/input3").to_string(),
+        "data/input3:12:7: invalid value: \"#1 @ 1,x: 2x2\""
+    );
+
+    let err = Error::invalid("no guards on record").in_file("data/input4");
+    assert_eq!(err.to_string(), "no guards on record");
+}
@@ -0,0 +1,140 @@
+//! Where puzzle inputs are read from
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use error::{Error, Result};
+
+/// Directory searched for `input{day}` files when nothing else is given.
+pub const DEFAULT_DIR: &str = "data";
+
+/// Environment variable overriding `DEFAULT_DIR`.
+pub const DIR_VAR: &str = "AOC_INPUT_DIR";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// A single file, used whatever the day
+    File(PathBuf),
+    /// A directory holding `input1`, `input2`, ...
+    Dir(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// Picks the source from an explicit `--input` (`-` meaning stdin), then
+    /// `--input-dir`, then the `AOC_INPUT_DIR` value, falling back to `data`.
+    pub fn resolve(input: Option<&str>, input_dir: Option<&str>, env_dir: Option<String>) -> Self {
+        match (input, input_dir, env_dir) {
+            (Some("-"), _, _) => Source::Stdin,
+            (Some(path), _, _) => Source::File(PathBuf::from(path)),
+            (None, Some(dir), _) => Source::Dir(PathBuf::from(dir)),
+            (None, None, Some(dir)) => Source::Dir(PathBuf::from(dir)),
+            (None, None, None) => Source::Dir(PathBuf::from(DEFAULT_DIR)),
+        }
+    }
+
+    /// Like `resolve`, reading `AOC_INPUT_DIR` from the environment.
+    pub fn from_env(input: Option<&str>, input_dir: Option<&str>) -> Self {
+        let env_dir = env::var(DIR_VAR).ok().filter(|d| !d.is_empty());
+        Source::resolve(input, input_dir, env_dir)
+    }
+
+    /// Path the input for `day` would be read from, `None` for stdin.
+    pub fn path_for(&self, day: u32) -> Option<PathBuf> {
+        match *self {
+            Source::File(ref path) => Some(path.clone()),
+            Source::Dir(ref dir) => Some(dir.join(format!("input{}", day))),
+            Source::Stdin => None,
+        }
+    }
+
+    /// Name of the input for `day` as shown in error messages.
+    pub fn name(&self, day: u32) -> String {
+        match self.path_for(day) {
+            Some(path) => path.display().to_string(),
+            None => "<stdin>".to_string(),
+        }
+    }
+
+    /// Reads the whole input for `day`. Errors name the path that was tried.
+    pub fn read(&self, day: u32) -> Result<String> {
+        let mut input = String::new();
+
+        let read = match self.path_for(day) {
+            Some(path) => fs::File::open(path).and_then(|mut f| f.read_to_string(&mut input)),
+            None => io::stdin().read_to_string(&mut input),
+        };
+
+        match read {
+            Ok(_) => Ok(input),
+            Err(source) => Err(Error::Io {
+                context: format!(
+                    "Failed to read input for day {} from {}",
+                    day,
+                    self.name(day)
+                ),
+                source,
+            }),
+        }
+    }
+
+    /// Reads the input for `day` and hands it to `f`, naming the input in
+    /// any parse error `f` returns.
+    pub fn with_input<T, F>(&self, day: u32, f: F) -> Result<T>
+    where
+        F: FnOnce(&str) -> Result<T>,
+    {
+        let input = self.read(day)?;
+        f(&input).map_err(|e| e.in_file(self.name(day)))
+    }
+}
+
+#[test]
+fn test_resolve() {
+    let env_dir = || Some("/tmp/aoc".to_string());
+
+    assert_eq!(
+        Source::resolve(Some("-"), Some("in"), env_dir()),
+        Source::Stdin
+    );
+    assert_eq!(
+        Source::resolve(Some("day5.txt"), Some("in"), env_dir()),
+        Source::File(PathBuf::from("day5.txt"))
+    );
+    assert_eq!(
+        Source::resolve(None, Some("in"), env_dir()),
+        Source::Dir(PathBuf::from("in"))
+    );
+    assert_eq!(
+        Source::resolve(None, None, env_dir()),
+        Source::Dir(PathBuf::from("/tmp/aoc"))
+    );
+    assert_eq!(
+        Source::resolve(None, None, None),
+        Source::Dir(PathBuf::from("data"))
+    );
+}
+
+#[test]
+fn test_path_for() {
+    let dir = Source::Dir(PathBuf::from("inputs"));
+    assert_eq!(dir.path_for(7), Some(PathBuf::from("inputs/input7")));
+
+    let file = Source::File(PathBuf::from("mine.txt"));
+    assert_eq!(file.path_for(7), Some(PathBuf::from("mine.txt")));
+
+    assert_eq!(Source::Stdin.path_for(7), None);
+}
+
+#[test]
+fn test_read_names_missing_path() {
+    let source = Source::Dir(PathBuf::from("no-such-dir"));
+    let err = source.read(3).unwrap_err();
+
+    assert!(err
+        .to_string()
+        .starts_with("Failed to read input for day 3 from no-such-dir/input3: "));
+    assert_eq!(err.exit_code(), 74);
+}
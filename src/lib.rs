@@ -0,0 +1,29 @@
+//! Advent of Code 2018 solutions.
+//!
+//! Each `dayN` module exposes the puzzle's building blocks along with a
+//! `DayN` type implementing [`solution::Solution`]. Every implemented day is
+//! listed in [`solution::registry`], and extra renderings of a day's input
+//! in [`view::registry`].
+
+#[macro_use]
+extern crate lazy_static;
+extern crate chrono;
+extern crate regex;
+
+pub mod answers;
+pub mod bench;
+pub mod calendar;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod error;
+pub mod input;
+pub mod solution;
+pub mod summary;
+pub mod view;
@@ -1,51 +1,198 @@
-#[macro_use]
-extern crate lazy_static;
+extern crate aoc2018;
 extern crate chrono;
 extern crate clap;
-extern crate regex;
 
+use aoc2018::answers::{self, Answers};
+use aoc2018::input::Source;
+use aoc2018::solution::Parts;
+use aoc2018::view::{self, Takes};
+use aoc2018::{bench, calendar, solution, summary};
 use chrono::prelude::*;
-use clap::{App, Arg};
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use clap::{App, Arg, ArgGroup};
+use std::fs;
+use std::process;
 
 fn valid_day(day: u32) -> bool {
     day >= 1 && day <= 31
 }
 
-fn solution_for(day: u32) {
+fn solution_for(day: u32, source: &Source, parts: Parts) {
     assert!(valid_day(day));
 
-    let input_file = &format!("data/input{}", day);
-
-    match day {
-        1 => day1::day1(input_file),
-        2 => day2::day2(input_file),
-        3 => day3::day3(input_file),
-        4 => day4::day4(input_file),
-        5 => day5::day5(input_file),
-        6 => day6::day6(input_file),
-        7 => day7::day7(input_file),
-        8 => day8::day8(input_file),
-        9 => day9::day9(input_file),
-        n => println!("Day {} is not available", n),
+    match solution::find(day) {
+        Some(d) => {
+            let answers = source
+                .with_input(day, |input| d.solution.run_parts(input, parts))
+                .unwrap_or_else(|e| {
+                    eprintln!("error: {}", e);
+                    process::exit(e.exit_code());
+                });
+            for (part, answer) in answers {
+                println!("Part {}: {}", part, answer);
+            }
+        }
+        None => {
+            eprintln!("Day {} is not available", day);
+            process::exit(2);
+        }
+    }
+}
+
+// Prints `view` of the day's input. Returns false if it isn't a view of
+// `day`.
+fn view_for(view: &view::View, day: u32, source: &Source, value: Option<&str>) -> bool {
+    if day != view.day {
+        eprintln!("--{} is only available for day {}", view.flag, view.day);
+        return false;
+    }
+
+    match source.with_input(day, |input| (view.render)(input, value)) {
+        Ok(out) => print!("{}", out),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(e.exit_code());
+        }
+    }
+
+    true
+}
+
+// Runs the given days and prints a summary table. Returns false if any of
+// them failed.
+fn summary_for(days: &[u32], source: &Source) -> bool {
+    let mut rows = vec![];
+
+    for &day in days {
+        match solution::find(day) {
+            Some(d) => rows.push(summary::run(&d, source)),
+            None => rows.push(summary::Row {
+                day,
+                result: Err("not available".to_string()),
+            }),
+        }
+    }
+
+    print!("{}", summary::render(&rows));
+
+    !rows.iter().any(|r| r.failed())
+}
+
+// Benchmarks the given days, prints the timings and writes them as JSON to
+// `out`. Returns false if any of them failed.
+fn bench_for(days: &[u32], source: &Source, options: &bench::Options, out: &str) -> bool {
+    let mut results = vec![];
+
+    for &day in days {
+        match solution::find(day) {
+            Some(d) => {
+                let result = summary::catch_panic(|| {
+                    source.with_input(day, |input| d.solution.bench(input, options))
+                });
+                results.push(bench::DayBench { day, result });
+            }
+            None => results.push(bench::DayBench {
+                day,
+                result: Err("not available".to_string()),
+            }),
+        }
+    }
+
+    print!("{}", bench::render(&results));
+
+    let json = bench::to_json(&results, options, &Utc::now().to_rfc3339());
+    if let Err(e) = fs::write(out, json) {
+        eprintln!("Failed to write {}: {}", out, e);
+        return false;
     }
+    println!("Benchmark results written to {}", out);
+
+    results.iter().all(|r| r.result.is_ok())
 }
 
+// Runs the given days and compares their answers with the answers file,
+// adding the ones not known yet when `record` is set. Returns false if any
+// day failed or gave a different answer.
+fn check_for(days: &[u32], source: &Source, parts: Parts, path: &str, record: bool) -> bool {
+    let mut answers = Answers::load(path).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(e.exit_code());
+    });
+
+    let mut ok = true;
+    let mut checks = vec![];
+
+    for &day in days {
+        match solution::find(day) {
+            Some(d) => {
+                let result = summary::catch_panic(|| {
+                    source.with_input(day, |input| d.solution.run_parts(input, parts))
+                });
+
+                match result {
+                    Ok(answered) => checks.extend(answers.check_parts(day, &answered)),
+                    Err(e) => {
+                        eprintln!("Day {} FAILED: {}", day, e);
+                        ok = false;
+                    }
+                }
+            }
+            None => {
+                eprintln!("Day {} FAILED: not available", day);
+                ok = false;
+            }
+        }
+    }
+
+    print!("{}", answers::render(&checks));
+
+    if record {
+        let added = answers.record(&checks);
+        if added > 0 {
+            if let Err(e) = answers.save(path) {
+                eprintln!("error: {}", e);
+                return false;
+            }
+        }
+        println!("Recorded {} new answer(s) in {}", added, path);
+    }
+
+    ok && !checks
+        .iter()
+        .any(|c| matches!(c.status, answers::Status::Fail { .. }))
+}
+
+fn count_arg(matches: &clap::ArgMatches, name: &str, default: u32) -> u32 {
+    match matches.value_of(name) {
+        Some(n) => n.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid --{}: {}", name, n);
+            process::exit(2);
+        }),
+        None => default,
+    }
+}
+
+// The latest unlocked puzzle that is implemented, explaining the choice on
+// stderr so that stdout only has the answers.
 fn advent_of_code_for_today() -> u32 {
-    Local::today().day()
+    let implemented: Vec<u32> = solution::registry().iter().map(|d| d.day).collect();
+
+    match calendar::default_day(Utc::now(), &implemented) {
+        Some(pick) => {
+            eprintln!("{}", pick);
+            pick.day
+        }
+        None => {
+            eprintln!("No days are implemented yet");
+            process::exit(1);
+        }
+    }
 }
 
 fn main() {
-    let matches = App::new("advent of rust 2018")
+    let views = view::registry();
+    let view_flags: Vec<&str> = views.iter().map(|v| v.flag).collect();
+
+    let mut app = App::new("advent of rust 2018")
         .version("1.0")
         .author("Chathura Colombage")
         .about("advent of code solutions for 2018 edition")
@@ -53,26 +200,182 @@ fn main() {
             Arg::with_name("day")
                 .short("d")
                 .long("day")
-                .help("run solution for the given day")
+                .help("run solution for the given day [default: latest unlocked puzzle that is implemented]")
                 .takes_value(true)
                 .required(false),
         )
+        .arg(
+            Arg::with_name("part")
+                .short("p")
+                .long("part")
+                .help("run only part 1 or part 2")
+                .takes_value(true)
+                .possible_values(&["1", "2"])
+                .conflicts_with_all(&["all", "days", "bench"])
+                .conflicts_with_all(&view_flags),
+        )
+        .arg(
+            Arg::with_name("all")
+                .short("a")
+                .long("all")
+                .help("run every implemented day and print a summary")
+                .conflicts_with_all(&["day", "days"])
+                .conflicts_with_all(&view_flags),
+        )
+        .arg(
+            Arg::with_name("days")
+                .long("days")
+                .help("run the given days, e.g. 1-9 or 1,3,5-7, and print a summary")
+                .takes_value(true)
+                .conflicts_with("day")
+                .conflicts_with_all(&view_flags),
+        )
+        .arg(
+            Arg::with_name("bench")
+                .long("bench")
+                .help("time parsing and both parts over repeated runs"),
+        )
+        .arg(
+            Arg::with_name("iterations")
+                .long("iterations")
+                .help("timed runs per step when benchmarking [default: 10]")
+                .takes_value(true)
+                .requires("bench"),
+        )
+        .arg(
+            Arg::with_name("warmup")
+                .long("warmup")
+                .help("untimed runs per step before timing [default: 1]")
+                .takes_value(true)
+                .requires("bench"),
+        )
+        .arg(
+            Arg::with_name("bench-out")
+                .long("bench-out")
+                .help("JSON file to write benchmark results to [default: bench.json]")
+                .takes_value(true)
+                .requires("bench"),
+        )
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .help("read the puzzle input from this file, or stdin for -")
+                .takes_value(true)
+                .conflicts_with_all(&["all", "days", "input-dir"]),
+        )
+        .arg(
+            Arg::with_name("input-dir")
+                .long("input-dir")
+                .help("read inputs named input1, input2, ... from this directory [env: AOC_INPUT_DIR] [default: data]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("compare answers with the answers file and report PASS/FAIL/NEW"),
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .help("like --check, also saving answers not in the answers file yet"),
+        )
+        .arg(
+            Arg::with_name("answers")
+                .long("answers")
+                .help("answers file used by --check and --record [default: answers.toml]")
+                .takes_value(true),
+        );
+
+    // Views and the other modes all exclude each other. Conflicts of a view
+    // are kept on the other side, as clap applies a member's conflicts to its
+    // whole group.
+    for v in &views {
+        let arg = Arg::with_name(v.flag).long(v.flag).help(v.help);
+
+        app = app.arg(match v.takes {
+            Takes::Nothing => arg,
+            Takes::Choice(values) => arg.takes_value(true).min_values(0).possible_values(values),
+            Takes::Count(name) => arg.takes_value(true).value_name(name),
+        });
+    }
+
+    let mut modes = vec!["bench", "check", "record"];
+    modes.extend(&view_flags);
+
+    let matches = app
+        .group(ArgGroup::with_name("mode").args(&modes))
         .get_matches();
 
-    let day: u32 = matches
-        .value_of("day")
-        .unwrap_or("0")
-        .parse()
-        .expect("Invalid day!");
+    let source = Source::from_env(matches.value_of("input"), matches.value_of("input-dir"));
+
+    let defaults = bench::Options::default();
+    let bench_options = bench::Options {
+        warmup: count_arg(&matches, "warmup", defaults.warmup),
+        iterations: count_arg(&matches, "iterations", defaults.iterations),
+    };
+    let bench_out = matches.value_of("bench-out").unwrap_or("bench.json");
 
-    if day == 0 {
-        let today = advent_of_code_for_today();
-        solution_for(today);
+    let answers_file = matches.value_of("answers").unwrap_or(answers::DEFAULT_FILE);
+    let record = matches.is_present("record");
+    let check = record || matches.is_present("check");
+
+    let parts = match matches.value_of("part") {
+        Some(part) => Parts::parse(part).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        }),
+        None => Parts::Both,
+    };
+
+    let many = matches.is_present("all") || matches.is_present("days");
+
+    let days = if many {
+        match matches.value_of("days") {
+            Some(spec) => solution::parse_days(spec).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(2);
+            }),
+            None => solution::registry().iter().map(|d| d.day).collect(),
+        }
     } else {
-        if valid_day(day) {
-            solution_for(day);
+        let day = matches.value_of("day").unwrap_or("0");
+        let day: u32 = day.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid day: {}", day);
+            process::exit(2);
+        });
+
+        let day = if day == 0 {
+            advent_of_code_for_today()
         } else {
-            println!("Invalid day: {}", day);
+            day
+        };
+
+        if !valid_day(day) {
+            eprintln!("Invalid day: {}", day);
+            process::exit(2);
         }
+
+        vec![day]
+    };
+
+    let ok = if let Some(v) = views.iter().find(|v| matches.is_present(v.flag)) {
+        if let Takes::Count(_) = v.takes {
+            count_arg(&matches, v.flag, 0);
+        }
+        view_for(v, days[0], &source, matches.value_of(v.flag))
+    } else if matches.is_present("bench") {
+        bench_for(&days, &source, &bench_options, bench_out)
+    } else if check {
+        check_for(&days, &source, parts, answers_file, record)
+    } else if many {
+        summary_for(&days, &source)
+    } else {
+        solution_for(days[0], &source, parts);
+        true
+    };
+
+    if !ok {
+        process::exit(1);
     }
 }
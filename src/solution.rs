@@ -0,0 +1,334 @@
+//! Common interface for the daily puzzles and the registry of implemented days
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use bench;
+use day1;
+use day2;
+use day3;
+use day4;
+use day5;
+use day6;
+use day7;
+use day8;
+use day9;
+use error::Result;
+
+/// A single part's answer. Puzzles ask for either a number or a string.
+/// Numbers are wide enough for any `i64` or `u64` answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(ref s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Number(i128::from(n))
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Number(i128::from(n))
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::Number(i128::from(n))
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        // usize is at most 64 bits wide, so this never wraps
+        Value::Number(n as i128)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+/// Answers to both parts of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub part1: Value,
+    pub part2: Value,
+}
+
+/// Which parts of a day to run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parts {
+    Both,
+    Part1,
+    Part2,
+}
+
+impl Parts {
+    /// Parses `1` or `2` as given to `--part`.
+    pub fn parse(s: &str) -> ::std::result::Result<Parts, String> {
+        match s.trim() {
+            "1" => Ok(Parts::Part1),
+            "2" => Ok(Parts::Part2),
+            _ => Err(format!("Invalid part: {}", s)),
+        }
+    }
+
+    /// Whether `part` is to be run.
+    pub fn includes(self, part: u8) -> bool {
+        match self {
+            Parts::Both => true,
+            Parts::Part1 => part == 1,
+            Parts::Part2 => part == 2,
+        }
+    }
+}
+
+/// A day's puzzle: parse the raw input once, then answer both parts from it
+pub trait Solution {
+    type Input;
+    type Part1: Into<Value>;
+    type Part2: Into<Value>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+    fn part1(&self, input: &Self::Input) -> Result<Self::Part1>;
+    fn part2(&self, input: &Self::Input) -> Result<Self::Part2>;
+
+    /// Parses `input` and answers both parts.
+    fn solve(&self, input: &str) -> Result<Answer> {
+        let parsed = self.parse(input)?;
+
+        Ok(Answer {
+            part1: self.part1(&parsed)?.into(),
+            part2: self.part2(&parsed)?.into(),
+        })
+    }
+}
+
+/// An answer along with how long each step took to get there.
+#[derive(Debug, Clone)]
+pub struct Timed {
+    pub answer: Answer,
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+impl Timed {
+    /// Time spent on both parts, excluding parsing.
+    pub fn solve(&self) -> Duration {
+        self.part1 + self.part2
+    }
+}
+
+/// Object safe view of a `Solution` so that days with different input and
+/// answer types can live in the same registry
+pub trait Runner {
+    fn run(&self, input: &str) -> Result<Answer>;
+    /// Parses `input` once and answers only the selected parts, returning
+    /// each answer with its part number.
+    fn run_parts(&self, input: &str, parts: Parts) -> Result<Vec<(u8, Value)>>;
+    fn run_timed(&self, input: &str) -> Result<Timed>;
+    fn bench(&self, input: &str, options: &bench::Options) -> Result<bench::Bench>;
+}
+
+impl<S: Solution> Runner for S {
+    fn run(&self, input: &str) -> Result<Answer> {
+        self.solve(input)
+    }
+
+    fn run_parts(&self, input: &str, parts: Parts) -> Result<Vec<(u8, Value)>> {
+        let parsed = self.parse(input)?;
+        let mut answers = vec![];
+
+        if parts.includes(1) {
+            answers.push((1, self.part1(&parsed)?.into()));
+        }
+        if parts.includes(2) {
+            answers.push((2, self.part2(&parsed)?.into()));
+        }
+
+        Ok(answers)
+    }
+
+    fn run_timed(&self, input: &str) -> Result<Timed> {
+        let start = Instant::now();
+        let parsed = self.parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let part1 = self.part1(&parsed)?.into();
+        let part1_time = start.elapsed();
+
+        let start = Instant::now();
+        let part2 = self.part2(&parsed)?.into();
+        let part2_time = start.elapsed();
+
+        Ok(Timed {
+            answer: Answer { part1, part2 },
+            parse,
+            part1: part1_time,
+            part2: part2_time,
+        })
+    }
+
+    fn bench(&self, input: &str, options: &bench::Options) -> Result<bench::Bench> {
+        // Fail early rather than timing the error path
+        let parsed = self.parse(input)?;
+        self.part1(&parsed)?;
+        self.part2(&parsed)?;
+
+        Ok(bench::Bench {
+            parse: bench::measure(options, || self.parse(input)),
+            part1: bench::measure(options, || self.part1(&parsed)),
+            part2: bench::measure(options, || self.part2(&parsed)),
+        })
+    }
+}
+
+/// A registered day and its solution.
+pub struct Day {
+    pub day: u32,
+    pub solution: Box<dyn Runner>,
+}
+
+impl Day {
+    fn new<S: Solution + 'static>(day: u32, solution: S) -> Self {
+        Day {
+            day,
+            solution: Box::new(solution),
+        }
+    }
+}
+
+/// All implemented days in ascending order. A new day only needs an entry here.
+pub fn registry() -> Vec<Day> {
+    vec![
+        Day::new(1, day1::Day1),
+        Day::new(2, day2::Day2),
+        Day::new(3, day3::Day3),
+        Day::new(4, day4::Day4),
+        Day::new(5, day5::Day5),
+        Day::new(6, day6::Day6),
+        Day::new(7, day7::Day7),
+        Day::new(8, day8::Day8),
+        Day::new(9, day9::Day9),
+    ]
+}
+
+/// Looks up a registered day.
+pub fn find(day: u32) -> Option<Day> {
+    registry().into_iter().find(|d| d.day == day)
+}
+
+/// Parses a day selection such as `1-9` or `1,3,5-7` into sorted, distinct
+/// day numbers.
+pub fn parse_days(spec: &str) -> ::std::result::Result<Vec<u32>, String> {
+    let mut days = vec![];
+
+    for part in spec.split(',').map(|p| p.trim()) {
+        let bounds: Vec<&str> = part.splitn(2, '-').map(|b| b.trim()).collect();
+
+        let (from, to) = match bounds.as_slice() {
+            [day] => (parse_day(day)?, parse_day(day)?),
+            [from, to] => (parse_day(from)?, parse_day(to)?),
+            _ => unreachable!(),
+        };
+
+        if from > to {
+            return Err(format!("Invalid day range: {}", part));
+        }
+
+        days.extend(from..=to);
+    }
+
+    days.sort();
+    days.dedup();
+
+    Ok(days)
+}
+
+fn parse_day(s: &str) -> ::std::result::Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("Invalid day: {}", s)),
+    }
+}
+
+#[test]
+fn test_registry_days_are_ordered_and_unique() {
+    let days: Vec<u32> = registry().iter().map(|d| d.day).collect();
+
+    for w in days.windows(2) {
+        assert!(w[0] < w[1]);
+    }
+}
+
+#[test]
+fn test_find() {
+    assert_eq!(find(5).map(|d| d.day), Some(5));
+    assert!(find(25).is_none());
+}
+
+#[test]
+fn test_parse_days() {
+    assert_eq!(parse_days("4"), Ok(vec![4]));
+    assert_eq!(parse_days("1-9"), Ok((1..10).collect()));
+    assert_eq!(parse_days("7, 1-3,2"), Ok(vec![1, 2, 3, 7]));
+    assert!(parse_days("9-1").is_err());
+    assert!(parse_days("0-3").is_err());
+    assert!(parse_days("26").is_err());
+    assert!(parse_days("a-b").is_err());
+    assert!(parse_days("").is_err());
+}
+
+#[test]
+fn test_value_display() {
+    assert_eq!(Value::from(-42).to_string(), "-42");
+    assert_eq!(Value::from("CABDFE".to_string()).to_string(), "CABDFE");
+    assert_eq!(Value::from(u64::MAX).to_string(), "18446744073709551615");
+    assert_eq!(Value::from(usize::MAX), Value::Number(usize::MAX as i128));
+}
+
+#[test]
+fn test_solve() {
+    let answer = day1::Day1.solve("+1\n-2\n+3\n+1").unwrap();
+
+    assert_eq!(
+        answer,
+        Answer {
+            part1: Value::Number(3),
+            part2: Value::Number(2),
+        }
+    );
+}
+
+#[test]
+fn test_run_parts() {
+    let day = find(1).unwrap();
+
+    assert_eq!(
+        day.solution.run_parts("+3\n-2", Parts::Part1).unwrap(),
+        vec![(1, Value::Number(1))]
+    );
+    assert_eq!(
+        day.solution.run_parts("+3\n-2", Parts::Both).unwrap(),
+        vec![(1, Value::Number(1)), (2, Value::Number(3))]
+    );
+    assert_eq!(Parts::parse("2"), Ok(Parts::Part2));
+    assert!(Parts::parse("3").is_err());
+}
@@ -0,0 +1,175 @@
+//! Running several days in one go and tabulating the results
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use error;
+use input::Source;
+use solution::{Day, Timed};
+
+/// Outcome of running a single day.
+pub struct Row {
+    pub day: u32,
+    pub result: Result<Timed, String>,
+}
+
+impl Row {
+    /// Whether the day errored or panicked.
+    pub fn failed(&self) -> bool {
+        self.result.is_err()
+    }
+}
+
+/// Runs a day on its input from `source`, turning an error or a panicking
+/// solution into a failed row so that the remaining days still get to run.
+pub fn run(day: &Day, source: &Source) -> Row {
+    Row {
+        day: day.day,
+        result: catch_panic(|| source.with_input(day.day, |input| day.solution.run_timed(input))),
+    }
+}
+
+/// Runs `f`, reporting its error or a panic as a message.
+pub fn catch_panic<T, F: FnOnce() -> error::Result<T>>(f: F) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(e) => Err(panic_message(&*e)),
+    }
+}
+
+fn panic_message(e: &(dyn Any + Send)) -> String {
+    if let Some(s) = e.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = e.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// Milliseconds with microsecond precision, e.g. `12.345ms`.
+pub fn format_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+/// Renders the rows as a plain text table.
+pub fn render(rows: &[Row]) -> String {
+    let header = ["Day", "Part 1", "Part 2", "Parse", "Solve"];
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| match row.result {
+            Ok(ref t) => vec![
+                row.day.to_string(),
+                t.answer.part1.to_string(),
+                t.answer.part2.to_string(),
+                format_duration(t.parse),
+                format_duration(t.solve()),
+            ],
+            Err(ref e) => vec![
+                row.day.to_string(),
+                format!("FAILED: {}", e),
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+            ],
+        })
+        .collect();
+
+    table(&header, &cells)
+}
+
+/// Renders a plain text table with a header row and left aligned columns.
+pub fn table(header: &[&str], cells: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in cells {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.len());
+        }
+    }
+
+    let line = |row: &[String]| -> String {
+        let padded: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &w)| format!("{:<w$}", cell, w = w))
+            .collect();
+        padded.join(" | ").trim_end().to_string()
+    };
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let separator: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+
+    let mut out = String::new();
+    out.push_str(&line(&header));
+    out.push('\n');
+    out.push_str(&separator.join("-+-"));
+    out.push('\n');
+    for row in cells {
+        out.push_str(&line(row));
+        out.push('\n');
+    }
+
+    out
+}
+
+#[test]
+fn test_render() {
+    use solution::{Answer, Value};
+
+    let rows = vec![
+        Row {
+            day: 1,
+            result: Ok(Timed {
+                answer: Answer {
+                    part1: Value::Number(470),
+                    part2: Value::Number(790),
+                },
+                parse: Duration::from_micros(1500),
+                part1: Duration::from_millis(2),
+                part2: Duration::from_millis(10),
+            }),
+        },
+        Row {
+            day: 7,
+            result: Err("not implemented".to_string()),
+        },
+    ];
+
+    assert_eq!(
+        render(&rows),
+        "Day | Part 1                  | Part 2 | Parse   | Solve\n\
+         ----+-------------------------+--------+---------+---------\n\
+         1   | 470                     | 790    | 1.500ms | 12.000ms\n\
+         7   | FAILED: not implemented | -      | -       | -\n"
+    );
+}
+
+#[test]
+fn test_catch_panic() {
+    use error::Error;
+
+    assert_eq!(catch_panic(|| Ok(3)), Ok(3));
+    assert_eq!(
+        catch_panic::<i32, _>(|| Err(Error::invalid("no answer"))),
+        Err("no answer".to_string())
+    );
+    assert_eq!(
+        catch_panic::<i32, _>(|| panic!("not implemented")),
+        Err("not implemented".to_string())
+    );
+}
+
+#[test]
+fn test_run_reports_missing_input() {
+    use solution;
+    use std::path::PathBuf;
+
+    let day = solution::find(1).unwrap();
+
+    assert!(run(&day, &Source::Dir(PathBuf::from("data")))
+        .result
+        .is_ok());
+    assert!(run(&day, &Source::Dir(PathBuf::from("no-such-dir"))).failed());
+}
@@ -0,0 +1,101 @@
+//! Extra ways of looking at a day's input besides its answers, each behind
+//! its own command line flag
+
+use day7;
+use day8;
+use day9;
+use error::Result;
+
+/// What a view's flag takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Takes {
+    /// Nothing, a plain flag
+    Nothing,
+    /// Optionally one of these values
+    Choice(&'static [&'static str]),
+    /// A number, shown under this name in the help
+    Count(&'static str),
+}
+
+/// A rendering of one day's input, e.g. `--gantt` for day 7.
+pub struct View {
+    /// Long flag selecting the view, without the dashes
+    pub flag: &'static str,
+    pub day: u32,
+    pub help: &'static str,
+    pub takes: Takes,
+    /// Renders the input given the flag's value, if any
+    pub render: fn(&str, Option<&str>) -> Result<String>,
+}
+
+/// All views. A new one only needs an entry here.
+pub fn registry() -> Vec<View> {
+    vec![
+        View {
+            flag: "dot",
+            day: 7,
+            help: "print the day 7 dependency graph as Graphviz DOT, coloured by completion step unless plain",
+            takes: Takes::Choice(&["steps", "plain"]),
+            render: day7::dot_view,
+        },
+        View {
+            flag: "critical-path",
+            day: 7,
+            help: "print the critical path and slack of each day 7 task",
+            takes: Takes::Nothing,
+            render: day7::critical_path_view,
+        },
+        View {
+            flag: "gantt",
+            day: 7,
+            help: "print the day 7 part 2 worker schedule as a table, or as csv",
+            takes: Takes::Choice(&["table", "csv"]),
+            render: day7::gantt_view,
+        },
+        View {
+            flag: "tree",
+            day: 8,
+            help: "print the day 8 license tree indented with the totals of each node, or as the flat stream",
+            takes: Takes::Choice(&["pretty", "flat"]),
+            render: day8::tree_view,
+        },
+        View {
+            flag: "trace",
+            day: 9,
+            help: "print the day 9 marble circle for the first N turns and the score of each player",
+            takes: Takes::Count("N"),
+            render: day9::trace_view,
+        },
+    ]
+}
+
+/// Looks up a view by its flag.
+pub fn find(flag: &str) -> Option<View> {
+    registry().into_iter().find(|v| v.flag == flag)
+}
+
+#[test]
+fn test_registry() {
+    use solution;
+
+    let views = registry();
+
+    for (i, v) in views.iter().enumerate() {
+        assert!(solution::find(v.day).is_some(), "--{}", v.flag);
+        assert!(views[..i].iter().all(|w| w.flag != v.flag), "--{}", v.flag);
+    }
+}
+
+#[test]
+fn test_render() {
+    let tree = find("tree").unwrap();
+
+    assert_eq!(
+        (tree.render)("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2", Some("flat")).unwrap(),
+        "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n"
+    );
+    assert!((tree.render)("2 3 0", None).is_err());
+
+    let trace = find("trace").unwrap();
+    assert!((trace.render)("9 players; last marble is worth 25 points", Some("x")).is_err());
+}
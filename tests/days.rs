@@ -0,0 +1,67 @@
+extern crate aoc2018;
+
+use aoc2018::day1::Frequency;
+use aoc2018::day5::polymer_react;
+use aoc2018::day7::{process_tasks, Day7, Dependency};
+use aoc2018::day8::Node;
+use aoc2018::solution::{self, Solution, Value};
+
+#[test]
+fn test_frequency() {
+    assert_eq!(Frequency::new().add_changes(&[1, -2, 3, 1]).value(), 3);
+    assert_eq!(
+        Frequency::new().first_repeating_value(&[1, -2, 3, 1]),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_polymer_react() {
+    assert_eq!(polymer_react("dabAcCaCBAcCcaDA"), "dabCBAcaDA");
+}
+
+#[test]
+fn test_task_map() {
+    let mut tasks = Day7
+        .parse(
+            "Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.",
+        )
+        .unwrap();
+
+    assert_eq!(process_tasks(tasks.clone()).unwrap().concat(), "CABDFE");
+
+    tasks.add(Dependency::new('C', 'E'));
+    let err = process_tasks(tasks).unwrap_err();
+    assert_eq!(err.cycle, vec!["A", "B", "E", "C", "A"]);
+    assert_eq!(err.blocked, vec!["D", "F"]);
+}
+
+#[test]
+fn test_node() {
+    let node = Node::from(&[2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]);
+
+    assert_eq!(node.children().len(), 2);
+    assert_eq!(node.metadata(), &[1, 1, 2]);
+    assert_eq!(node.sum_metadata(), 138);
+    assert_eq!(node.node_value(), 66);
+}
+
+#[test]
+fn test_solutions_from_registry() {
+    let day = solution::find(5).unwrap();
+    let answer = day.solution.run("dabAcCaCBAcCcaDA\n").unwrap();
+
+    assert_eq!(answer.part1, Value::Number(10));
+    assert_eq!(answer.part2, Value::Number(4));
+
+    let answer = aoc2018::day2::Day2
+        .solve("abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz")
+        .unwrap();
+    assert_eq!(answer.part2, Value::Text("fgij".to_string()));
+}